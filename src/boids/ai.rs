@@ -1,4 +1,5 @@
-use bevy::prelude::{Commands, Component, Entity, Query, Reflect, Res, ResMut, Time, With};
+use bevy::math::Vec2;
+use bevy::prelude::{Commands, Component, Entity, EventWriter, Query, Reflect, Res, ResMut, Time, With, Without};
 use bevy::log::{debug, trace};
 use big_brain::prelude::{ActionBuilder, ActionSpan, Actor, Score, ScorerBuilder, ScorerSpan};
 use big_brain::actions::ActionState;
@@ -6,10 +7,14 @@ use bevy_xpbd_2d::components::Position;
 use bevy_rand::prelude::GlobalEntropy;
 use rand_chacha::ChaCha8Rng;
 use rand::Rng;
+use rand::distributions::WeightedIndex;
 use crate::boids::components::{BoidAttack, BoidStuff};
+use crate::components::effects::StatusEffects;
 use crate::components::general::Health;
 use crate::components::general::Prey;
-use crate::components::quad::QuadCoord;
+use crate::components::player::Player;
+use crate::components::quad::{QuadCoord, QuadStore};
+use crate::events::damage::PlayerDamagedEvent;
 
 pub fn hunger_system(time: Res<Time>, mut hungers: Query<&mut Hunger>) {
     for mut hungry in &mut hungers {
@@ -23,10 +28,10 @@ pub fn hunger_system(time: Res<Time>, mut hungers: Query<&mut Hunger>) {
 
 pub fn attack_and_eat_action_system(
     mut query: Query<(&Actor, &mut ActionState, &AttackAndEat, &ActionSpan)>,
-    mut boid_query: Query<(&HuntTarget, &mut BoidStuff, &mut BoidAttack, &mut Hunger, &Position)>,
-    mut target_query: Query<(&mut Health, &Position)>,
-    time: Res<Time>,
+    mut boid_query: Query<(&HuntTarget, &mut BoidStuff, &mut BoidAttack, &mut Hunger, &Position, &StatusEffects)>,
+    mut target_query: Query<(&mut Health, &Position, Option<&mut StatusEffects>, Option<&Player>), Without<BoidAttack>>,
     mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    mut player_damaged_writer: EventWriter<PlayerDamagedEvent>,
     mut commands: Commands
 ) {
     for (Actor(actor), mut state, _, _) in &mut query {
@@ -47,24 +52,34 @@ pub fn attack_and_eat_action_system(
             }
             ActionState::Executing => {
                 trace!("Do we have a hunt target?");
-                if let Ok((hunt_target, mut hunter_boid, mut boid_attack, mut hunger, hunter_position)) = boid_query.get_mut(*actor) {
-                    if let Ok((mut health, hunted_position)) = target_query.get_mut(hunt_target.0) {
+                if let Ok((hunt_target, mut hunter_boid, mut boid_attack, mut hunger, hunter_position, boid_effects)) = boid_query.get_mut(*actor) {
+                    if let Ok((mut health, hunted_position, target_effects, player)) = target_query.get_mut(hunt_target.0) {
                         let delta = hunted_position.0 - hunter_position.0;
                         hunter_boid.desired_direction = delta.normalize_or_zero();
 
-                        boid_attack.cool_down -= time.delta().as_secs_f32();
-                        if boid_attack.cool_down < 0.0 {
-                            boid_attack.cool_down = boid_attack.cool_down_default.clone();
-                            if rng.gen_range(1..=100) <= boid_attack.skill_level {
-                                debug!("We hit our prey!");
-                                let damage =  rng.gen_range( boid_attack.max_damage.clone());
-                                health.health -= damage;
-                                hunger.hunger -= (damage * 2 ) as f32;
-                                if hunger.hunger < 10.0 || health.health <= 0 {
-                                    commands.entity(*actor).remove::<HuntTarget>();
-                                    *state = ActionState::Success;
+                        if boid_effects.is_stunned() {
+                            continue;
+                        }
+                        if let Some(damage) = boid_attack.try_attack(&mut *rng) {
+                            debug!("We hit our prey!");
+                            health.health -= damage;
+                            if let Some(mut target_effects) = target_effects {
+                                for effect in boid_attack.effects.iter() {
+                                    target_effects.apply(effect, *actor);
                                 }
                             }
+                            if player.is_some() {
+                                player_damaged_writer.send(PlayerDamagedEvent {
+                                    player: hunt_target.0,
+                                    boid: *actor,
+                                    amount: damage,
+                                });
+                            }
+                            hunger.hunger -= (damage * 2 ) as f32;
+                            if hunger.hunger < 10.0 || health.health <= 0 {
+                                commands.entity(*actor).remove::<HuntTarget>();
+                                *state = ActionState::Success;
+                            }
                         }
 
                     } else {
@@ -111,12 +126,16 @@ pub fn hunt_prey_action_system(
             ActionState::Executing => {
                 trace!("Do we have a hunt target?");
                 if let Ok((hunt_target, mut hunter_boid, hunter_position)) = boid_query.get_mut(*actor) {
-                    let hunted_position = hunt_target_position_query.get(hunt_target.0).unwrap();
-                    let delta = hunted_position.0 - hunter_position.0;
-                    if delta.length_squared() < 5.0 {
-                        *state = ActionState::Success
+                    if let Ok(hunted_position) = hunt_target_position_query.get(hunt_target.0) {
+                        let delta = hunted_position.0 - hunter_position.0;
+                        if delta.length_squared() < 5.0 {
+                            *state = ActionState::Success
+                        } else {
+                            hunter_boid.desired_direction = delta.normalize_or_zero();
+                        }
                     } else {
-                        hunter_boid.desired_direction = delta.normalize_or_zero();
+                        debug!("Our hunting target is gone");
+                        *state = ActionState::Failure;
                     }
                 } else {
                     debug!("We did not have a hunting target");
@@ -168,8 +187,7 @@ pub fn find_prey_action_system(
                 if let Ok((position, quad_coord)) = pos_query.get(*actor) {
                     debug!("Searching for prey in quadrant: {:?}", quad_coord);
                     if let Some((entity, _, _)) = prey_iter.filter(|(_, prey_quad_coord, _)| {
-                        prey_quad_coord.x >= quad_coord.x - 1 && prey_quad_coord.x <= quad_coord.x + 1 &&
-                            prey_quad_coord.y >= quad_coord.y - 1 && prey_quad_coord.y <= quad_coord.y + 1
+                        prey_quad_coord.is_adjacent(quad_coord)
                     }).min_by_key(|(_, _, prey_position)| {
                         let delta = prey_position.0 - position.0;
                         let distance_sq: f32 = delta.length_squared();
@@ -181,8 +199,9 @@ pub fn find_prey_action_system(
                     } else {
                         debug!("No prey found!");
                         /*
-                        Now we would want the AI to try to move this boid to some other quadrant.
-                        We will do that later.
+                        Failing here fails the whole hunting sequence. Brains that start
+                        the sequence with WanderToQuadrant will move this boid somewhere else
+                        before looking again.
                          */
                         *state = ActionState::Failure;
                     }
@@ -200,6 +219,118 @@ pub fn find_prey_action_system(
     }
 }
 
+pub fn wander_action_system(
+    mut query: Query<(&Actor, &mut ActionState, &mut Wander, &ActionSpan)>,
+    mut boid_query: Query<&mut BoidStuff>,
+    time: Res<Time>,
+    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+) {
+    for (Actor(actor), mut state, mut wander, span) in &mut query {
+        let _guard = span.span().enter();
+
+        match *state {
+            ActionState::Requested => {
+                debug!("Nothing better to do, wandering off");
+                if let Ok(mut boid_stuff) = boid_query.get_mut(*actor) {
+                    boid_stuff.desired_direction = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)).normalize_or_zero();
+                }
+                wander.time_left = wander.duration;
+                *state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                wander.time_left -= time.delta_seconds();
+                if wander.time_left <= 0.0 {
+                    if let Ok(mut boid_stuff) = boid_query.get_mut(*actor) {
+                        boid_stuff.desired_direction = Vec2::ZERO;
+                    }
+                    *state = ActionState::Success;
+                }
+            }
+            // All Actions should make sure to handle cancellations!
+            ActionState::Cancelled => {
+                debug!("Action was cancelled. Considering this a failure.");
+                *state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn wander_to_quadrant_action_system(
+    mut query: Query<(&Actor, &mut ActionState, &mut WanderToQuadrant, &ActionSpan)>,
+    mut boid_query: Query<(&Position, &QuadCoord, &mut BoidStuff)>,
+    prey_query: Query<&QuadCoord, With<Prey>>,
+    quad_store: Res<QuadStore>,
+    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+) {
+    for (Actor(actor), mut state, mut wander, span) in &mut query {
+        let _guard = span.span().enter();
+
+        match *state {
+            ActionState::Requested => {
+                if let Ok((position, quad_coord, _)) = boid_query.get(*actor) {
+                    if prey_query.iter().any(|prey_quad_coord| prey_quad_coord.is_adjacent(quad_coord)) {
+                        debug!("Prey is close by, no need to move");
+                        *state = ActionState::Success;
+                    } else {
+                        wander.destination = pick_destination(position.0, wander.explore_range, &quad_store, &prey_query, &mut rng);
+                        debug!("Moving to {:?} to look for prey", wander.destination);
+                        *state = ActionState::Executing;
+                    }
+                } else {
+                    debug!("No position found for actor!");
+                    *state = ActionState::Failure;
+                }
+            }
+            ActionState::Executing => {
+                if let Ok((position, _, mut boid_stuff)) = boid_query.get_mut(*actor) {
+                    let delta = wander.destination - position.0;
+                    if delta.length() < quad_store.quad_size {
+                        debug!("Arrived, time to look for prey again");
+                        boid_stuff.desired_direction = Vec2::ZERO;
+                        *state = ActionState::Success;
+                    } else {
+                        boid_stuff.desired_direction = delta.normalize_or_zero();
+                    }
+                } else {
+                    debug!("No position found for actor!");
+                    *state = ActionState::Failure;
+                }
+            }
+            // All Actions should make sure to handle cancellations!
+            ActionState::Cancelled => {
+                debug!("Action was cancelled. Considering this a failure.");
+                *state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+/*
+Quads holding more prey are more likely to be picked. If there is no prey in
+the store at all, we just head off in some direction, explore_range quads away.
+ */
+fn pick_destination(
+    from: Vec2,
+    explore_range: f32,
+    quad_store: &QuadStore,
+    prey_query: &Query<&QuadCoord, With<Prey>>,
+    rng: &mut GlobalEntropy<ChaCha8Rng>,
+) -> Vec2 {
+    let prey_quads = quad_store.entities
+        .iter()
+        .map(|(coord, entities)| (coord, entities.iter().filter(|entity| prey_query.contains(**entity)).count()))
+        .filter(|(_, prey_count)| *prey_count > 0)
+        .collect::<Vec<_>>();
+
+    if let Ok(weights) = WeightedIndex::new(prey_quads.iter().map(|(_, prey_count)| *prey_count)) {
+        prey_quads[rng.sample(weights)].0.center(quad_store.quad_size)
+    } else {
+        let direction = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)).try_normalize().unwrap_or(Vec2::Y);
+        from + direction * explore_range * quad_store.quad_size
+    }
+}
 
 // Looks familiar? It's a lot like Actions!
 pub fn hunger_scorer_system(
@@ -246,6 +377,32 @@ pub struct AttackAndEat {
 #[derive(Clone, Component, Debug, ActionBuilder)]
 pub struct FindPrey {}
 
+/// Fallback for when nothing scores: head off in a random direction for a while.
+#[derive(Clone, Component, Debug, ActionBuilder)]
+pub struct Wander {
+    pub duration: f32,
+    pub time_left: f32,
+}
+
+impl Wander {
+    pub fn new(duration: f32) -> Self {
+        Self { duration, time_left: duration }
+    }
+}
+
+/// Moves the boid to a quadrant likely to hold prey, succeeding straight away if prey is already near.
+#[derive(Clone, Component, Debug, ActionBuilder)]
+pub struct WanderToQuadrant {
+    pub explore_range: f32,
+    pub destination: Vec2,
+}
+
+impl WanderToQuadrant {
+    pub fn new(explore_range: f32) -> Self {
+        Self { explore_range, destination: Vec2::ZERO }
+    }
+}
+
 impl Hunger {
     pub fn new(hunger: f32, per_second: f32) -> Self {
         Self { hunger, per_second }
@@ -0,0 +1,141 @@
+use bevy::asset::{AssetLoader, BoxedFuture, Handle, LoadContext, LoadedAsset};
+use bevy::prelude::{Component, Resource};
+use bevy::reflect::{TypePath, TypeUuid};
+use big_brain::actions::{Steps, StepsBuilder};
+use big_brain::pickers::{FirstToScore, Highest};
+use big_brain::thinker::{Thinker, ThinkerBuilder};
+use serde::Deserialize;
+use crate::boids::ai::{AttackAndEat, FindPrey, Hunt, Hungry, Wander, WanderToQuadrant};
+
+/*
+Boid brains are described in a `.brains.ron` asset instead of being built in code, like so:
+
+(
+    brains: [
+        (
+            name: "hunter",
+            picker: FirstToScore(threshold: 0.8),
+            choices: [
+                (scorer: Hungry, action: (label: "Hunt And Eat", steps: [WanderToQuadrant(explore_range: 4.0), FindPrey, Hunt, AttackAndEat(per_second: 10.0)])),
+            ],
+            otherwise: Some((label: "Wander", steps: [Wander(duration: 3.0)])),
+        ),
+    ],
+)
+ */
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum PickerDef {
+    FirstToScore { threshold: f32 },
+    Highest,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum ScorerDef {
+    Hungry,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum ActionDef {
+    FindPrey,
+    Hunt,
+    AttackAndEat { per_second: f32 },
+    Wander { duration: f32 },
+    WanderToQuadrant { explore_range: f32 },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct StepsDef {
+    pub label: String,
+    pub steps: Vec<ActionDef>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ChoiceDef {
+    pub scorer: ScorerDef,
+    pub action: StepsDef,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BoidBrainDef {
+    pub name: String,
+    pub picker: PickerDef,
+    pub choices: Vec<ChoiceDef>,
+    pub otherwise: Option<StepsDef>,
+}
+
+#[derive(Deserialize, Clone, Debug, TypeUuid, TypePath)]
+#[uuid = "5b1f9a52-8f3e-4c1b-9d3a-6f2b0c7e4a11"]
+pub struct BoidBrainDefs {
+    pub brains: Vec<BoidBrainDef>,
+}
+
+impl BoidBrainDefs {
+    pub fn get(&self, name: &str) -> Option<&BoidBrainDef> {
+        self.brains.iter().find(|brain| brain.name == name)
+    }
+}
+
+impl StepsDef {
+    pub fn build_steps(&self) -> StepsBuilder {
+        self.steps
+            .iter()
+            .fold(Steps::build().label(&self.label), |steps, step| match step {
+                ActionDef::FindPrey => steps.step(FindPrey {}),
+                ActionDef::Hunt => steps.step(Hunt {}),
+                ActionDef::AttackAndEat { per_second } => steps.step(AttackAndEat { per_second: *per_second }),
+                ActionDef::Wander { duration } => steps.step(Wander::new(*duration)),
+                ActionDef::WanderToQuadrant { explore_range } => steps.step(WanderToQuadrant::new(*explore_range)),
+            })
+    }
+}
+
+impl BoidBrainDef {
+    pub fn build_thinker(&self) -> ThinkerBuilder {
+        let thinker = Thinker::build().label(&self.name);
+        let thinker = match self.picker {
+            PickerDef::FirstToScore { threshold } => thinker.picker(FirstToScore { threshold }),
+            PickerDef::Highest => thinker.picker(Highest),
+        };
+        let thinker = self.choices
+            .iter()
+            .fold(thinker, |thinker, choice| match choice.scorer {
+                ScorerDef::Hungry => thinker.when(Hungry, choice.action.build_steps()),
+            });
+        match &self.otherwise {
+            Some(otherwise) => thinker.otherwise(otherwise.build_steps()),
+            None => thinker,
+        }
+    }
+}
+
+/// Which brain, by name, a boid should get once the brain definitions have loaded.
+#[derive(Component, Clone, Debug)]
+pub struct BoidBrain(pub String);
+
+#[derive(Resource)]
+pub struct BoidBrains {
+    pub path: String,
+    pub handle: Handle<BoidBrainDefs>,
+}
+
+#[derive(Default)]
+pub struct BoidBrainsLoader;
+
+impl AssetLoader for BoidBrainsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let brains = ron::de::from_bytes::<BoidBrainDefs>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(brains));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["brains.ron"]
+    }
+}
@@ -1,10 +1,12 @@
-use bevy::prelude::{Bundle, Component, default, Reflect};
+use bevy::prelude::{Bundle, Component, default, Entity, Reflect};
+use rand::Rng;
 use bevy_xpbd_2d::math::Vector2;
 use std::ops::Range;
 use bevy::core::Name;
 use bevy::math::Vec2;
 use bevy_xpbd_2d::components::{Collider, CollisionLayers, Position, RigidBody};
-use crate::components::general::Health;
+use crate::components::effects::{StatusEffect, StatusEffects};
+use crate::components::general::{Health, Prey};
 use crate::{Layer, METERS_PER_PIXEL};
 use crate::components::quad::QuadCoord;
 
@@ -59,7 +61,97 @@ pub struct BoidAttack {
     pub max_damage: Range<i32>,
     pub cool_down: f32,
     pub cool_down_default: f32,
-    pub skill_level: i32
+    pub skill_level: i32,
+    /// Applied to whatever the boid bites.
+    pub effects: Vec<StatusEffect>,
+}
+
+impl BoidAttack {
+    /// Attacks if the cool down has run out. Hits `skill_level` percent of the time,
+    /// returning the damage rolled.
+    pub fn try_attack(&mut self, rng: &mut impl Rng) -> Option<i32> {
+        if self.cool_down >= 0.0 {
+            return None;
+        }
+        self.cool_down = self.cool_down_default;
+        (rng.gen_range(1..=100) <= self.skill_level).then(|| rng.gen_range(self.max_damage.clone()))
+    }
+}
+
+/// Velocity a hit gave the boid, it moves with this instead of steering until it has decayed,
+/// losing `decay` of it every second.
+#[derive(Reflect, Clone, Debug, Component)]
+pub struct Knockback {
+    pub velocity: Vector2,
+    pub decay: f32,
+}
+
+impl Default for Knockback {
+    fn default() -> Self {
+        Self {
+            velocity: Vector2::ZERO,
+            decay: 6.0,
+        }
+    }
+}
+
+impl Knockback {
+    /// Below this speed the boid is back in control.
+    const MIN_SPEED: f32 = 0.5;
+
+    pub fn push(&mut self, direction: Vector2, speed: f32) {
+        self.velocity += direction.normalize_or_zero() * speed;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.velocity.length() >= Self::MIN_SPEED
+    }
+
+    pub fn decay(&mut self, delta: f32) {
+        self.velocity *= (-self.decay * delta).exp();
+        if !self.is_active() {
+            self.velocity = Vector2::ZERO;
+        }
+    }
+}
+
+/// What is left of a dead boid. Hungry boids eat it like any other prey, it is gone when
+/// its health has been eaten or after `time_left` seconds.
+#[derive(Clone, Debug, Component)]
+pub struct Corpse {
+    pub time_left: f32,
+}
+
+#[derive(Bundle)]
+pub struct CorpseBundle {
+    pub name: Name,
+    pub corpse: Corpse,
+    pub prey: Prey,
+    pub health: Health,
+    pub position: Position,
+    pub quad_coord: QuadCoord,
+}
+
+impl CorpseBundle {
+    pub fn new(position: Vec2, meat: i32, time_left: f32) -> Self {
+        Self {
+            name: Name::from("Corpse"),
+            corpse: Corpse { time_left },
+            prey: Prey {},
+            health: Health {
+                health: meat,
+                max: meat,
+            },
+            position: Position::from(position),
+            quad_coord: QuadCoord::default(),
+        }
+    }
+}
+
+/// Set while the boid is touching a player, so it can keep biting it.
+#[derive(Clone, Debug, Component)]
+pub struct PlayerContact {
+    pub player: Entity,
 }
 
 
@@ -93,7 +185,9 @@ pub struct BoidBundle {
     pub collider: Collider,
     pub collision_layers: CollisionLayers,
     pub boid_attack: BoidAttack,
-    pub boid_stuff: BoidStuff
+    pub boid_stuff: BoidStuff,
+    pub status_effects: StatusEffects,
+    pub knockback: Knockback,
 }
 impl BoidBundle {
     pub fn new(
@@ -109,7 +203,7 @@ impl BoidBundle {
             name: Name::from(name),
             direction_control: BoidDirection {
                 force_scale: 5.0,
-                direction: direction.clone(),
+                direction,
                 ..default()
             },
             boid: Boid {},
@@ -120,12 +214,15 @@ impl BoidBundle {
                  cool_down: 0.0,
                  cool_down_default,
                  skill_level,
+                 effects: vec![StatusEffect::bleed(3.0, 1.0)],
              },
+            status_effects: StatusEffects::default(),
+            knockback: Knockback::default(),
              rigid_body: RigidBody::Kinematic,
             quad_coord: QuadCoord::default(),
             position: Position::from(position),
             collider: Collider::cuboid(16.0 * METERS_PER_PIXEL, 8.0 * METERS_PER_PIXEL),
-            collision_layers: CollisionLayers::new([Layer::Boid], [Layer::Player, Layer::Bullet]),
+            collision_layers: CollisionLayers::new([Layer::Boid], [Layer::Player, Layer::Bullet, Layer::Walls, Layer::Water]),
         }
     }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
-pub(crate) mod components;
-pub(crate) mod systems;
-pub(crate) mod ai;
-pub(crate) mod resources;
-
+pub mod components;
+pub mod systems;
+pub mod ai;
+pub mod resources;
+pub mod brain;
@@ -8,11 +8,13 @@ pub struct BoidGenerationSettings {
     pub boids_to_generate: i32,
     pub max_boids: usize,
     pub min_boids: usize,
-    pub generate_boids: bool
+    pub generate_boids: bool,
+    pub initial_boids: usize,
+    pub brain: String,
 }
 
 impl BoidGenerationSettings {
-    pub fn new(cool_down: f32, to_generate: i32, min_boids: usize, max_boids: usize) -> Self {
+    pub fn new(cool_down: f32, to_generate: i32, min_boids: usize, max_boids: usize, initial_boids: usize, brain: String) -> Self {
         Self {
             cool_down,
             time_left: cool_down,
@@ -20,7 +22,24 @@ impl BoidGenerationSettings {
             boids_to_generate: to_generate,
             min_boids,
             max_boids,
-            generate_boids: true
+            generate_boids: true,
+            initial_boids,
+            brain,
         }
     }
-}
\ No newline at end of file
+
+    /// Starts counting and generating boids from scratch, for a new game.
+    pub fn reset(&mut self) {
+        self.time_left = self.cool_down;
+        self.counting_time_left = self.cool_down * 10.0;
+        self.generate_boids = true;
+    }
+}
+
+/// Dead boids leave a corpse with `meat` health behind for `lifetime` seconds, none at all
+/// if `lifetime` is zero.
+#[derive(Resource, Clone, Debug)]
+pub struct CorpseSettings {
+    pub lifetime: f32,
+    pub meat: i32,
+}
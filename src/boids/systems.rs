@@ -1,7 +1,9 @@
-use bevy::prelude::{Commands, default, Entity, info, Query, Res, ResMut, SpriteBundle, Transform, With};
+use bevy::asset::{AssetServer, Assets};
+use bevy::prelude::{Commands, default, Entity, EventReader, EventWriter, info, Or, Query, Res, ResMut, Transform, TransformBundle, warn, With, Without};
+use bevy::utils::HashMap;
+use bevy_xpbd_2d::prelude::CollisionEnded;
 use bevy_xpbd_2d::components::{Position, Rotation};
 use bevy::math::{Vec2, Vec3};
-use bevy::asset::AssetServer;
 use bevy_rand::prelude::GlobalEntropy;
 use rand_chacha::ChaCha8Rng;
 use bevy_xpbd_2d::math::Vector2;
@@ -9,14 +11,20 @@ use rand::Rng;
 use std::ops::AddAssign;
 use bevy::time::Time;
 use crate::METERS_PER_PIXEL;
+use big_brain::thinker::ThinkerBuilder;
 use crate::boids::ai::Hunger;
-use crate::boids::components::{Boid, BoidBundle, BoidDirection, BoidStuff};
-use crate::boids::resources::BoidGenerationSettings;
+use crate::boids::brain::{BoidBrain, BoidBrainDefs, BoidBrains};
+use crate::boids::components::{Boid, BoidAttack, BoidBundle, BoidDirection, BoidStuff, Corpse, CorpseBundle, Knockback, PlayerContact};
+use crate::boids::resources::{BoidGenerationSettings, CorpseSettings};
+use crate::components::effects::StatusEffects;
+use crate::components::general::{Health, SpriteTexture};
+use crate::components::player::Player;
+use crate::events::collisions::BoidHitPlayerEvent;
+use crate::events::damage::{BoidDiedEvent, DamageEvent, PlayerDamagedEvent};
 use crate::components::quad::{QuadCoord, QuadStore};
 
 pub fn spawn_more_boids(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
     time: Res<Time>,
     mut boid_settings: ResMut<BoidGenerationSettings>,
@@ -38,29 +46,10 @@ pub fn spawn_more_boids(
                 let x = rng.gen_range(-200..200) as f32;
                 let y = rng.gen_range(-100..100) as f32;
 
-                // let hunt_and_eat = Steps::build()
-                //     .label("Hunt And Eat")
-                //     // Try to find prey...
-                //     .step(FindPrey {})
-                //     // ...hunting it...
-                //     .step(Hunt {})
-                //     // ...and eating it.
-                //     .step(AttackAndEat { per_second: 10.0 });
-                //
-                // let thinker = Thinker::build()
-                //     .label("Boid Thinker")
-                //     .picker(FirstToScore { threshold: 0.8 })
-                //     // Technically these are supposed to be ActionBuilders and
-                //     // ScorerBuilders, but our Clone impls simplify our code here.
-                //     .when(
-                //         Hungry,
-                //         hunt_and_eat,
-                //     );
-
                 commands
                     .spawn((
-                        // thinker,
-                        // Hunger::new(75.0, rng.gen_range(1..100) as f32 / 100.0),
+                        BoidBrain(boid_settings.brain.clone()),
+                        Hunger::new(75.0, rng.gen_range(1..100) as f32 / 100.0),
                         BoidBundle::new(
                             format!("Boid {}", n),
                             Vec2::new(x, y),
@@ -76,8 +65,8 @@ pub fn spawn_more_boids(
                                 ..default()
                             },
                         ),
-                        SpriteBundle {
-                            transform: Transform::from_xyz(
+                        TransformBundle::from_transform(
+                            Transform::from_xyz(
                                 x,
                                 y,
                                 2.0,
@@ -86,10 +75,8 @@ pub fn spawn_more_boids(
                                     METERS_PER_PIXEL,
                                     METERS_PER_PIXEL,
                                     1.0,
-                                )),
-                            texture: asset_server.load("sprites/boid.png"),
-                            ..default()
-                        },
+                                ))),
+                        SpriteTexture("sprites/boid.png"),
                     ));
             }
         }
@@ -99,34 +86,15 @@ pub fn spawn_more_boids(
 
 pub fn spawn_boids(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    boid_settings: Res<BoidGenerationSettings>,
 ) {
-    for n in 0..100 {
+    for n in 0..boid_settings.initial_boids {
         let x = rng.gen_range(-200..200) as f32;
         let y = rng.gen_range(-100..100) as f32;
-        // let hunt_and_eat = Steps::build()
-        //     .label("Hunt And Eat")
-        //     // Try to find prey...
-        //     .step(FindPrey {})
-        //     // ...hunting it...
-        //     .step(Hunt {})
-        //     // ...and eating it.
-        //     .step(AttackAndEat { per_second: 10.0 });
-        //
-        // let thinker = Thinker::build()
-        //     .label("Boid Thinker")
-        //     .picker(FirstToScore { threshold: 0.8 })
-        //     // Technically these are supposed to be ActionBuilders and
-        //     // ScorerBuilders, but our Clone impls simplify our code here.
-        //     .when(
-        //         Hungry,
-        //         hunt_and_eat,
-        //     );
-
         commands
             .spawn((
-                // thinker,
+                BoidBrain(boid_settings.brain.clone()),
                 Hunger::new(75.0, rng.gen_range(1..100) as f32 / 100.0),
                 BoidBundle::new(
                     format!("Boid {}", n),
@@ -143,8 +111,8 @@ pub fn spawn_boids(
                         ..default()
                     },
                 ),
-                SpriteBundle {
-                    transform: Transform::from_xyz(
+                TransformBundle::from_transform(
+                    Transform::from_xyz(
                         x,
                         y,
                         2.0,
@@ -153,22 +121,50 @@ pub fn spawn_boids(
                             METERS_PER_PIXEL,
                             METERS_PER_PIXEL,
                             1.0,
-                        )),
-                    texture: asset_server.load("sprites/boid.png"),
-                    ..default()
-                },
+                        ))),
+                SpriteTexture("sprites/boid.png"),
             ));
     }
 }
 
+pub fn load_boid_brains(
+    asset_server: Res<AssetServer>,
+    mut boid_brains: ResMut<BoidBrains>,
+) {
+    let handle = asset_server.load(boid_brains.path.as_str());
+    boid_brains.handle = handle;
+}
+
+/// Gives every boid the thinker for its `BoidBrain` as soon as the brain definitions are loaded.
+pub fn attach_boid_thinkers(
+    mut commands: Commands,
+    boid_brains: Res<BoidBrains>,
+    brain_defs: Res<Assets<BoidBrainDefs>>,
+    query: Query<(Entity, &BoidBrain), Without<ThinkerBuilder>>,
+) {
+    let Some(brain_defs) = brain_defs.get(&boid_brains.handle) else { return; };
+    for (entity, boid_brain) in query.iter() {
+        if let Some(brain_def) = brain_defs.get(&boid_brain.0) {
+            commands.entity(entity).insert(brain_def.build_thinker());
+        } else {
+            warn!("No boid brain called {} in {}", boid_brain.0, boid_brains.path);
+            commands.entity(entity).remove::<BoidBrain>();
+        }
+    }
+}
+
+/// Stunned boids don't turn.
 pub fn boid_steering(mut query: Query<(
     &mut BoidDirection,
     &mut Rotation,
     &BoidStuff,
     &Transform,
-    &Position), With<Boid>>) {
-    let mut iter = query.iter_mut();
-    while let Some((mut direction_control, mut rotation, boid_stuff, transform, position)) = iter.next() {
+    &Position,
+    &StatusEffects), With<Boid>>) {
+    for (mut direction_control, mut rotation, boid_stuff, transform, position, status_effects) in query.iter_mut() {
+        if status_effects.is_stunned() {
+            continue;
+        }
         direction_control.up = Vec2::new(transform.up().x, transform.up().y);
         let cohesion_direction = (boid_stuff.flock_center - position.0).normalize_or_zero() * boid_stuff.cohesion_factor;
         let separation_direction = if boid_stuff.separation_boids > 0 { boid_stuff.separation_vector.normalize_or_zero() * boid_stuff.separation_factor } else { Vec2::ZERO };
@@ -198,8 +194,7 @@ pub fn quad_boid_flocking(
     other_query: Query<(&Position, &BoidDirection)>,
     quad_store: Res<QuadStore>,
 ) {
-    let mut iter = query.iter_mut();
-    while let Some((entity, position, quad_coord, mut boid_stuff)) = iter.next() {
+    for (entity, position, quad_coord, mut boid_stuff) in query.iter_mut() {
         boid_stuff.flock_center = Vector2::ZERO;
         boid_stuff.cohesion_boids = 0;
         boid_stuff.separation_vector = Vector2::ZERO;
@@ -208,9 +203,9 @@ pub fn quad_boid_flocking(
         boid_stuff.alignment_direction = Vector2::ZERO;
 
         let quad_coords =
-            (-1..=1).map(|x|
+            (-1..=1).flat_map(|x|
                 (-1..=1).map(move |y|
-                    QuadCoord::new(quad_coord.x + x, quad_coord.y + y))).flatten().collect::<Vec<_>>();
+                    QuadCoord::new(quad_coord.x + x, quad_coord.y + y))).collect::<Vec<_>>();
 
         let others = quad_coords
             .iter()
@@ -252,3 +247,146 @@ pub fn quad_boid_flocking(
         }
     }
 }
+
+pub fn boid_attack_cool_down_system(time: Res<Time>, mut query: Query<&mut BoidAttack>) {
+    for mut boid_attack in query.iter_mut() {
+        boid_attack.cool_down -= time.delta_seconds();
+    }
+}
+
+/// Keeps track of which boids are touching the player.
+pub fn boid_contact_listener(
+    mut boid_hit_player_event_reader: EventReader<BoidHitPlayerEvent>,
+    mut collision_ended_reader: EventReader<CollisionEnded>,
+    contact_query: Query<&PlayerContact>,
+    mut commands: Commands,
+) {
+    for BoidHitPlayerEvent { boid, player } in boid_hit_player_event_reader.iter() {
+        if let Some(mut boid) = commands.get_entity(*boid) {
+            boid.insert(PlayerContact { player: *player });
+        }
+    }
+    for CollisionEnded(entity1, entity2) in collision_ended_reader.iter() {
+        for (boid, other) in [(*entity1, *entity2), (*entity2, *entity1)] {
+            if contact_query.get(boid).is_ok_and(|contact| contact.player == other) {
+                commands.entity(boid).remove::<PlayerContact>();
+            }
+        }
+    }
+}
+
+pub fn knockback_decay_system(time: Res<Time>, mut query: Query<&mut Knockback>) {
+    for mut knockback in query.iter_mut() {
+        if knockback.is_active() {
+            knockback.decay(time.delta_seconds());
+        }
+    }
+}
+
+/// Boids touching the player bite it whenever their attack has cooled down. Stunned boids don't.
+pub fn boid_contact_damage_system(
+    mut boid_query: Query<(Entity, &PlayerContact, &mut BoidAttack, &StatusEffects), Without<Player>>,
+    mut player_query: Query<(&mut Health, &mut StatusEffects), With<Player>>,
+    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    mut player_damaged_writer: EventWriter<PlayerDamagedEvent>,
+    mut commands: Commands,
+) {
+    for (boid, contact, mut boid_attack, boid_effects) in boid_query.iter_mut() {
+        let Ok((mut health, mut player_effects)) = player_query.get_mut(contact.player) else {
+            commands.entity(boid).remove::<PlayerContact>();
+            continue;
+        };
+        if boid_effects.is_stunned() {
+            continue;
+        }
+        if let Some(amount) = boid_attack.try_attack(&mut *rng) {
+            health.health -= amount;
+            for effect in boid_attack.effects.iter() {
+                player_effects.apply(effect, boid);
+            }
+            player_damaged_writer.send(PlayerDamagedEvent {
+                player: contact.player,
+                boid,
+                amount,
+            });
+        }
+    }
+}
+
+/// Clears out the boids and corpses of the last game, `spawn_boids` brings in new boids.
+pub fn reset_boids(
+    mut commands: Commands,
+    mut boid_settings: ResMut<BoidGenerationSettings>,
+    boid_query: Query<Entity, Or<(With<Boid>, With<Corpse>)>>,
+) {
+    for boid in boid_query.iter() {
+        commands.entity(boid).despawn();
+    }
+    boid_settings.reset();
+}
+
+/*
+Whatever hits a boid only takes its health, boids die here: the last damage a boid
+took before running out of health is what killed it. They are taken out of the
+`QuadStore` right away, so nothing finds them there, and may leave a corpse behind.
+ */
+pub fn boid_death_system(
+    mut damage_event_reader: EventReader<DamageEvent>,
+    boid_query: Query<(&Position, &Health), With<Boid>>,
+    corpse_settings: Res<CorpseSettings>,
+    mut quad_store: ResMut<QuadStore>,
+    mut boid_died_writer: EventWriter<BoidDiedEvent>,
+    mut commands: Commands,
+) {
+    let mut killing_blows = HashMap::new();
+    for DamageEvent { shooter, target, weapon, .. } in damage_event_reader.iter() {
+        killing_blows.insert(*target, (*shooter, weapon.clone()));
+    }
+    for (boid, (killer, weapon)) in killing_blows {
+        let Ok((position, health)) = boid_query.get(boid) else { continue; };
+        if health.health > 0 {
+            continue;
+        }
+        boid_died_writer.send(BoidDiedEvent {
+            boid,
+            killer,
+            weapon,
+            position: position.0,
+        });
+        quad_store.remove(boid);
+        commands.entity(boid).despawn();
+        if corpse_settings.lifetime > 0.0 {
+            commands.spawn((
+                CorpseBundle::new(position.0, corpse_settings.meat, corpse_settings.lifetime),
+                TransformBundle::from_transform(
+                    Transform::from_xyz(
+                        position.0.x,
+                        position.0.y,
+                        1.5,
+                    )
+                        .with_scale(Vec3::new(
+                            METERS_PER_PIXEL,
+                            METERS_PER_PIXEL,
+                            1.0,
+                        ))),
+                SpriteTexture("sprites/boid.png"),
+            ));
+        }
+    }
+}
+
+/// Corpses rot away, or are eaten, and are gone.
+pub fn corpse_system(
+    time: Res<Time>,
+    mut corpse_query: Query<(Entity, &mut Corpse, &Health)>,
+    mut quad_store: ResMut<QuadStore>,
+    mut commands: Commands,
+) {
+    for (entity, mut corpse, health) in corpse_query.iter_mut() {
+        corpse.time_left -= time.delta_seconds();
+        if corpse.time_left <= 0.0 || health.health <= 0 {
+            quad_store.remove(entity);
+            commands.entity(entity).despawn();
+        }
+    }
+}
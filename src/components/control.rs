@@ -1,4 +1,4 @@
-use bevy::prelude::{Component, Reflect};
+use bevy::prelude::{Component, Reflect, Resource};
 use bevy_xpbd_2d::math::Vector2;
 use bevy_xpbd_2d::components::Rotation;
 
@@ -16,6 +16,15 @@ pub struct CycleWeapon {
     pub direction: CycleDirection,
 }
 
+/// Selects the weapon in `slot`, counting from zero.
+#[derive(Component, Clone)]
+pub struct SelectWeapon {
+    pub slot: usize,
+}
+
+#[derive(Component, Clone)]
+pub struct ReloadWeapon {}
+
 #[derive(Reflect)]
 #[derive(Copy, Clone, Debug, Component)]
 pub struct PlayerControl {
@@ -41,3 +50,54 @@ impl Default for PlayerControl {
         }
     }
 }
+
+/// What drives the `PlayerControl` of the player.
+#[derive(Clone, Debug)]
+pub enum PlayerController {
+    Keyboard,
+    Idle,
+    Scripted(Vec<ScriptStep>),
+}
+
+impl PlayerController {
+    /// Walks the player around a square while shooting outwards, for headless runs.
+    pub fn strafe_and_shoot() -> Self {
+        PlayerController::Scripted(
+            [Vector2::X, Vector2::Y, Vector2::NEG_X, Vector2::NEG_Y]
+                .iter()
+                .map(|direction| ScriptStep {
+                    duration: 2.0,
+                    direction: *direction,
+                    aim_direction: direction.perp(),
+                    trigger_pulled: true,
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScriptStep {
+    pub duration: f32,
+    pub direction: Vector2,
+    pub aim_direction: Vector2,
+    pub trigger_pulled: bool,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct PlayerScript {
+    pub steps: Vec<ScriptStep>,
+    pub current: usize,
+    pub time_left: f32,
+}
+
+impl PlayerScript {
+    pub fn new(steps: Vec<ScriptStep>) -> Self {
+        let time_left = steps.first().map(|step| step.duration).unwrap_or(0.0);
+        Self {
+            steps,
+            current: 0,
+            time_left,
+        }
+    }
+}
@@ -1,15 +1,245 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Component, Entity, Reflect};
+use serde::Deserialize;
 
-#[derive(Component, Default)]
-pub struct Stunned {
-    pub cooldown: f32,
+/// Most bleeds a target can have at once, a new one replaces the one closest to running out.
+pub const MAX_BLEED_STACKS: usize = 5;
+
+/// What a status effect does while it lasts.
+///
+/// Only one of each kind is active at a time, applying it again keeps the longer duration and
+/// the stronger effect, except for `Bleed`, which stacks up to `MAX_BLEED_STACKS` times.
+#[derive(Reflect, Clone, Debug, PartialEq, Deserialize)]
+pub enum StatusEffectKind {
+    /// Can't move, turn or attack.
+    Stun,
+    /// Moves at `factor` of the normal speed.
+    Slow { factor: f32 },
+    Burning { damage_per_second: f32 },
+    Bleed { damage_per_second: f32 },
+    /// Explosions and hits don't push it around.
+    KnockbackImmunity,
+}
+
+#[derive(Reflect, Clone, Debug, PartialEq, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub duration: f32,
+}
+
+impl StatusEffect {
+    pub fn stun(duration: f32) -> Self {
+        Self { kind: StatusEffectKind::Stun, duration }
+    }
+
+    pub fn bleed(duration: f32, damage_per_second: f32) -> Self {
+        Self { kind: StatusEffectKind::Bleed { damage_per_second }, duration }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            StatusEffectKind::Stun => "Stun",
+            StatusEffectKind::Slow { .. } => "Slow",
+            StatusEffectKind::Burning { .. } => "Burning",
+            StatusEffectKind::Bleed { .. } => "Bleed",
+            StatusEffectKind::KnockbackImmunity => "Knockback Immunity",
+        }
+    }
+
+    /// Returns what is wrong with the effect, if anything.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.duration <= 0.0 {
+            return Err(format!("{} must last above zero seconds, was {}", self.name(), self.duration));
+        }
+        match self.kind {
+            StatusEffectKind::Slow { factor } if !(0.0..=1.0).contains(&factor) => {
+                Err(format!("Slow factor must be between 0 and 1, was {}", factor))
+            }
+            StatusEffectKind::Burning { damage_per_second } | StatusEffectKind::Bleed { damage_per_second } if damage_per_second < 0.0 => {
+                Err(format!("{} must not heal, damage per second was {}", self.name(), damage_per_second))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn damage_per_second(&self) -> f32 {
+        match self.kind {
+            StatusEffectKind::Burning { damage_per_second } | StatusEffectKind::Bleed { damage_per_second } => damage_per_second,
+            _ => 0.0,
+        }
+    }
+
+    fn same_kind(&self, other: &StatusEffect) -> bool {
+        std::mem::discriminant(&self.kind) == std::mem::discriminant(&other.kind)
+    }
+}
+
+#[derive(Reflect, Clone, Debug)]
+pub struct ActiveStatusEffect {
+    pub effect: StatusEffect,
+    pub time_left: f32,
+    /// Whoever applied it, damage over time is theirs.
+    pub source: Entity,
+    /// Damage below a whole point carried over to the next tick.
+    damage_carried: f32,
 }
 
-impl Stunned {
-    pub fn default() -> Self {
-        Self { cooldown: 1.0 }
+/// Damage over time dealt by an effect during one tick.
+pub struct EffectDamage {
+    pub source: Entity,
+    pub amount: i32,
+    pub effect: &'static str,
+}
+
+#[derive(Component, Reflect, Clone, Debug, Default)]
+pub struct StatusEffects {
+    pub active: Vec<ActiveStatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: &StatusEffect, source: Entity) {
+        let applied = ActiveStatusEffect {
+            effect: effect.clone(),
+            time_left: effect.duration,
+            source,
+            damage_carried: 0.0,
+        };
+        if let StatusEffectKind::Bleed { .. } = effect.kind {
+            let bleeds = self.active.iter().filter(|active| active.effect.same_kind(effect)).count();
+            if bleeds >= MAX_BLEED_STACKS {
+                if let Some(index) = self.active
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, active)| active.effect.same_kind(effect))
+                    .min_by(|(_, a), (_, b)| a.time_left.total_cmp(&b.time_left))
+                    .map(|(index, _)| index) {
+                    self.active[index] = applied;
+                }
+            } else {
+                self.active.push(applied);
+            }
+            return;
+        }
+
+        let Some(active) = self.active.iter_mut().find(|active| active.effect.same_kind(effect)) else {
+            self.active.push(applied);
+            return;
+        };
+        active.time_left = active.time_left.max(effect.duration);
+        let stronger = match (&active.effect.kind, &effect.kind) {
+            (StatusEffectKind::Slow { factor: current }, StatusEffectKind::Slow { factor: new }) => new < current,
+            _ => effect.damage_per_second() > active.effect.damage_per_second(),
+        };
+        if stronger {
+            active.effect.kind = effect.kind.clone();
+            active.source = source;
+        }
     }
-    pub fn new(cooldown: f32) -> Self {
-        Self { cooldown }
+
+    /// Counts down every effect, dropping the ones that ran out, and returns the damage
+    /// the burns and bleeds did meanwhile.
+    pub fn tick(&mut self, delta: f32) -> Vec<EffectDamage> {
+        let mut damage = Vec::new();
+        for active in self.active.iter_mut() {
+            let seconds = delta.min(active.time_left.max(0.0));
+            active.time_left -= delta;
+            active.damage_carried += active.effect.damage_per_second() * seconds;
+            let amount = active.damage_carried.floor() as i32;
+            if amount > 0 {
+                active.damage_carried -= amount as f32;
+                damage.push(EffectDamage {
+                    source: active.source,
+                    amount,
+                    effect: active.effect.name(),
+                });
+            }
+        }
+        self.active.retain(|active| active.time_left > 0.0);
+        damage
     }
-}
\ No newline at end of file
+
+    pub fn is_stunned(&self) -> bool {
+        self.has(|kind| matches!(kind, StatusEffectKind::Stun))
+    }
+
+    pub fn is_knockback_immune(&self) -> bool {
+        self.has(|kind| matches!(kind, StatusEffectKind::KnockbackImmunity))
+    }
+
+    /// Factor of the normal speed we move at, nothing at all while stunned.
+    pub fn speed_factor(&self) -> f32 {
+        if self.is_stunned() {
+            return 0.0;
+        }
+        self.active
+            .iter()
+            .filter_map(|active| match active.effect.kind {
+                StatusEffectKind::Slow { factor } => Some(factor),
+                _ => None,
+            })
+            .fold(1.0, f32::min)
+    }
+
+    fn has(&self, select: impl Fn(&StatusEffectKind) -> bool) -> bool {
+        self.active.iter().any(|active| select(&active.effect.kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> Entity {
+        Entity::from_raw(7)
+    }
+
+    fn slow(duration: f32, factor: f32) -> StatusEffect {
+        StatusEffect { kind: StatusEffectKind::Slow { factor }, duration }
+    }
+
+    #[test]
+    fn reapplying_keeps_the_longer_duration_and_the_stronger_effect() {
+        let mut status_effects = StatusEffects::default();
+        status_effects.apply(&slow(3.0, 0.8), source());
+        status_effects.apply(&slow(1.0, 0.5), source());
+        assert_eq!(status_effects.active.len(), 1);
+        assert_eq!(status_effects.active[0].time_left, 3.0);
+        assert_eq!(status_effects.speed_factor(), 0.5);
+
+        status_effects.apply(&slow(5.0, 0.9), source());
+        assert_eq!(status_effects.active[0].time_left, 5.0);
+        assert_eq!(status_effects.speed_factor(), 0.5);
+    }
+
+    #[test]
+    fn bleeds_stack_up_to_the_limit_and_replace_the_one_closest_to_running_out() {
+        let mut status_effects = StatusEffects::default();
+        for i in 0..MAX_BLEED_STACKS {
+            status_effects.apply(&StatusEffect::bleed(1.0 + i as f32, 1.0), source());
+        }
+        status_effects.apply(&StatusEffect::bleed(10.0, 1.0), source());
+        let mut durations = status_effects.active.iter().map(|active| active.time_left).collect::<Vec<_>>();
+        durations.sort_by(f32::total_cmp);
+        assert_eq!(durations, vec![2.0, 3.0, 4.0, 5.0, 10.0]);
+    }
+
+    #[test]
+    fn ticking_deals_damage_over_time_and_drops_effects_that_ran_out() {
+        let mut status_effects = StatusEffects::default();
+        status_effects.apply(&StatusEffect { kind: StatusEffectKind::Burning { damage_per_second: 1.5 }, duration: 2.0 }, source());
+        status_effects.apply(&StatusEffect::stun(0.5), source());
+        assert!(status_effects.is_stunned());
+        assert_eq!(status_effects.speed_factor(), 0.0);
+
+        let mut damage = 0;
+        for _ in 0..6 {
+            for effect_damage in status_effects.tick(0.5) {
+                assert_eq!(effect_damage.source, source());
+                assert_eq!(effect_damage.effect, "Burning");
+                damage += effect_damage.amount;
+            }
+        }
+        assert_eq!(damage, 3);
+        assert!(!status_effects.is_stunned());
+        assert!(status_effects.active.is_empty());
+    }
+}
@@ -1,5 +1,9 @@
+use bevy::core::Name;
+use bevy::math::Vec2;
 use bevy::prelude::{Bundle, Component, Reflect, SpriteSheetBundle};
 use bevy_ecs_ldtk::{LdtkEntity, LdtkIntCell};
+use bevy_xpbd_2d::components::{Collider, CollisionLayers, Position, RigidBody, Sensor};
+use crate::Layer;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Wall;
@@ -9,6 +13,35 @@ pub struct WallBundle {
     wall: Wall,
 }
 
+/// A rectangle of merged wall cells, the cells themselves have no colliders.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct WallCollider;
+
+#[derive(Bundle)]
+pub struct WallColliderBundle {
+    pub name: Name,
+    pub wall: Wall,
+    pub wall_collider: WallCollider,
+    pub rigid_body: RigidBody,
+    pub position: Position,
+    pub collider: Collider,
+    pub collision_layers: CollisionLayers,
+}
+
+impl WallColliderBundle {
+    pub fn new(center: Vec2, size: Vec2) -> Self {
+        Self {
+            name: Name::from("Wall"),
+            wall: Wall,
+            wall_collider: WallCollider,
+            rigid_body: RigidBody::Static,
+            position: Position::from(center),
+            collider: Collider::cuboid(size.x, size.y),
+            collision_layers: CollisionLayers::new([Layer::Walls], [Layer::Player, Layer::Boid, Layer::Bullet]),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Water;
 
@@ -17,12 +50,51 @@ pub struct WaterBundle {
     water: Water,
 }
 
+/// A rectangle of merged water cells that whatever overlaps it is in.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct WaterArea;
+
+#[derive(Bundle)]
+pub struct WaterAreaBundle {
+    pub name: Name,
+    pub water: Water,
+    pub water_area: WaterArea,
+    pub rigid_body: RigidBody,
+    pub sensor: Sensor,
+    pub position: Position,
+    pub collider: Collider,
+    pub collision_layers: CollisionLayers,
+}
+
+impl WaterAreaBundle {
+    pub fn new(center: Vec2, size: Vec2) -> Self {
+        Self {
+            name: Name::from("Water"),
+            water: Water,
+            water_area: WaterArea,
+            rigid_body: RigidBody::Static,
+            sensor: Sensor,
+            position: Position::from(center),
+            collider: Collider::cuboid(size.x, size.y),
+            collision_layers: CollisionLayers::new([Layer::Water], [Layer::Player, Layer::Boid, Layer::Bullet]),
+        }
+    }
+}
+
 #[derive(Component, Clone)]
 pub struct Prey {}
 
 #[derive(Component)]
 pub struct AimLine {}
 
+/// Full screen overlay shown while in one of the menu states, despawned when leaving it.
+#[derive(Component)]
+pub struct StateScreen {}
+
+/// Texture to draw the entity with, attached as a sprite only when running with graphics.
+#[derive(Component, Clone)]
+pub struct SpriteTexture(pub &'static str);
+
 
 #[derive(Component)]
 pub struct GameCam {}
@@ -38,8 +110,11 @@ pub struct MapEntity {
     sprite_bundle: SpriteSheetBundle,
 }
 
+/// Overlapping at least one `WaterArea`, `areas` counts how many.
 #[derive(Component)]
-pub struct InWater {}
+pub struct InWater {
+    pub areas: usize,
+}
 
 #[derive(Bundle, LdtkIntCell)]
 pub struct IntCell {
@@ -0,0 +1,122 @@
+use bevy::asset::{AssetLoader, BoxedFuture, Handle, LoadContext, LoadedAsset};
+use bevy::math::Vec2;
+use bevy::prelude::Resource;
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy_ecs_ldtk::ldtk::Level;
+use serde::Deserialize;
+
+/// The LDtk project levels are loaded from, and the int grid values of its terrain.
+#[derive(Resource, Clone, Debug)]
+pub struct LevelSettings {
+    pub path: String,
+    pub wall: i32,
+    pub water: i32,
+    pub player_start: i32,
+}
+
+/// The data of the selected level, once the project it is in has been loaded.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LoadedLevel {
+    pub level: Option<Level>,
+}
+
+/// Where the player starts in the current level, once it has been loaded and if it has a start.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LevelStart {
+    pub position: Option<Vec2>,
+}
+
+/// How a kind of terrain affects whatever is in it. Speeds are factors of the normal speed,
+/// anything left out of the terrain file leaves things as they are.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TerrainEffects {
+    pub player_speed: f32,
+    pub projectile_speed: f32,
+    pub boids_avoid: bool,
+}
+
+impl Default for TerrainEffects {
+    fn default() -> Self {
+        Self {
+            player_speed: 1.0,
+            projectile_speed: 1.0,
+            boids_avoid: false,
+        }
+    }
+}
+
+/*
+The effects of each kind of terrain are read from a `.terrain.ron` asset, and hot
+reloaded into the Terrain resource when the file changes:
+
+(
+    water: (
+        player_speed: 0.5,
+        projectile_speed: 0.5,
+        boids_avoid: true,
+    ),
+)
+ */
+#[derive(Resource, Clone, Debug, Deserialize, TypeUuid, TypePath)]
+#[uuid = "04d442dc-21db-4e8a-bb92-cb1642aa763a"]
+pub struct Terrain {
+    pub water: TerrainEffects,
+}
+
+/// The terrain the game ships with, used until the asset at `LevelPlugin::terrain_path` has loaded.
+impl Default for Terrain {
+    fn default() -> Self {
+        ron::de::from_str(include_str!("../../assets/terrain/terrain.terrain.ron"))
+            .expect("assets/terrain/terrain.terrain.ron should be valid")
+    }
+}
+
+#[derive(Resource)]
+pub struct TerrainFile {
+    pub path: String,
+    pub handle: Handle<Terrain>,
+}
+
+#[derive(Default)]
+pub struct TerrainLoader;
+
+impl AssetLoader for TerrainLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let path = load_context.path().display().to_string();
+            let terrain = ron::de::from_bytes::<Terrain>(bytes)
+                .map_err(|error| bevy::asset::Error::msg(format!("{}: {}", path, error)))?;
+            load_context.set_default_asset(LoadedAsset::new(terrain));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["terrain.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_terrain_is_the_shipped_asset() {
+        let terrain = Terrain::default();
+        assert!(terrain.water.player_speed < 1.0);
+        assert!(terrain.water.boids_avoid);
+    }
+
+    #[test]
+    fn terrain_effects_left_out_change_nothing() {
+        let terrain = ron::de::from_str::<Terrain>("(water: (player_speed: 0.25))").unwrap();
+        assert_eq!(terrain.water.player_speed, 0.25);
+        assert_eq!(terrain.water.projectile_speed, 1.0);
+        assert!(!terrain.water.boids_avoid);
+    }
+}
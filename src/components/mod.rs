@@ -1,9 +1,8 @@
-pub(crate) mod player;
-pub(crate) mod control;
-pub(crate) mod weapon;
-pub(crate) mod effects;
-pub(crate) mod general;
-pub(crate) mod quad;
-
-
-
+pub mod player;
+pub mod control;
+pub mod weapon;
+pub mod effects;
+pub mod general;
+pub mod quad;
+pub mod pickup;
+pub mod level;
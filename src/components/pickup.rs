@@ -0,0 +1,186 @@
+use bevy::core::Name;
+use bevy::log::warn;
+use bevy::math::Vec2;
+use bevy::prelude::{Bundle, Component, Reflect, Resource};
+use bevy_ecs_ldtk::EntityInstance;
+use bevy_ecs_ldtk::prelude::LdtkFields;
+use bevy_xpbd_2d::components::{Collider, CollisionLayers, Position, RigidBody, Sensor};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use crate::Layer;
+
+/// Something the player collects by walking over it.
+/// Ammo without a weapon name goes to the weapon the player has equipped.
+#[derive(Component, Clone, Debug, Reflect)]
+pub enum Pickup {
+    Weapon(String),
+    Ammo {
+        weapon: Option<String>,
+        amount: i32,
+    },
+    Health(i32),
+}
+
+impl Pickup {
+    /*
+    LDtk "Pickup" entities have a "kind" field that is Weapon, Ammo or Health,
+    a "weapon" field naming the weapon and an "amount" field. Health and ammo without
+    an amount would give nothing, so those are reported.
+     */
+    pub fn from_entity_instance(entity_instance: &EntityInstance) -> Self {
+        let weapon = entity_instance
+            .get_string_field("weapon")
+            .ok()
+            .filter(|weapon| !weapon.is_empty())
+            .cloned();
+        let amount = || entity_instance.get_int_field("amount").copied().unwrap_or_else(|_| {
+            warn!("LDtk pickup {} has no amount, it gives nothing", entity_instance.iid);
+            0
+        });
+        match entity_instance.get_string_field("kind").map(|kind| kind.as_str()) {
+            Ok("Weapon") => Pickup::Weapon(weapon.unwrap_or_default()),
+            Ok("Health") => Pickup::Health(amount()),
+            _ => Pickup::Ammo { weapon, amount: amount() },
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Pickup::Weapon(weapon) => weapon.clone(),
+            Pickup::Ammo { weapon: Some(weapon), .. } => format!("{} Ammo", weapon),
+            Pickup::Ammo { weapon: None, .. } => "Ammo".to_string(),
+            Pickup::Health(_) => "Health".to_string(),
+        }
+    }
+}
+
+/// Dropped by a boid rather than placed in the level, these are cleared out for a new game.
+#[derive(Component, Clone, Debug)]
+pub struct Dropped;
+
+/// Placed in the level, these are put back for a new game and when the level changes.
+#[derive(Component, Clone, Debug)]
+pub struct Placed;
+
+/// One of the things a `LootTable` drops, picked `weight` times as often as an entry of weight one.
+#[derive(Clone, Debug)]
+pub struct LootEntry {
+    pub pickup: Pickup,
+    pub weight: u32,
+}
+
+/// What dying boids may leave behind. Every dead boid gets `rolls` tries at dropping
+/// something, each succeeding with `chance` and picking an entry by weight.
+#[derive(Resource, Clone, Debug)]
+pub struct LootTable {
+    pub chance: f64,
+    pub rolls: u32,
+    pub entries: Vec<LootEntry>,
+}
+
+impl Default for LootTable {
+    fn default() -> Self {
+        Self {
+            chance: 0.05,
+            rolls: 1,
+            entries: vec![
+                LootEntry {
+                    pickup: Pickup::Ammo {
+                        weapon: None,
+                        amount: 24,
+                    },
+                    weight: 3,
+                },
+                LootEntry {
+                    pickup: Pickup::Health(25),
+                    weight: 1,
+                },
+            ],
+        }
+    }
+}
+
+impl LootTable {
+    pub fn roll(&self, rng: &mut impl Rng) -> Vec<Pickup> {
+        let Ok(weights) = WeightedIndex::new(self.entries.iter().map(|entry| entry.weight)) else { return vec![]; };
+        let mut pickups = Vec::new();
+        for _ in 0..self.rolls {
+            if rng.gen_bool(self.chance) {
+                pickups.push(self.entries[weights.sample(rng)].pickup.clone());
+            }
+        }
+        pickups
+    }
+}
+
+#[derive(Bundle, Clone)]
+pub struct PickupBundle {
+    pub name: Name,
+    pub pickup: Pickup,
+    pub position: Position,
+    pub rigid_body: RigidBody,
+    pub sensor: Sensor,
+    pub collider: Collider,
+    pub collision_layers: CollisionLayers,
+}
+
+impl PickupBundle {
+    pub fn new(pickup: Pickup, position: Vec2) -> Self {
+        Self {
+            name: Name::from(pickup.name()),
+            pickup,
+            position: Position(position),
+            rigid_body: RigidBody::Static,
+            sensor: Sensor,
+            collider: Collider::ball(1.0),
+            collision_layers: CollisionLayers::new([Layer::Pickup], [Layer::Player]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use super::*;
+
+    fn loot_table(chance: f64, rolls: u32) -> LootTable {
+        LootTable {
+            chance,
+            rolls,
+            ..LootTable::default()
+        }
+    }
+
+    #[test]
+    fn every_roll_that_hits_drops_one_pickup() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        assert_eq!(loot_table(1.0, 3).roll(&mut rng).len(), 3);
+        assert!(loot_table(0.0, 3).roll(&mut rng).is_empty());
+    }
+
+    #[test]
+    fn entries_are_picked_by_weight() {
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        let loot_table = LootTable {
+            entries: vec![
+                LootEntry { pickup: Pickup::Health(25), weight: 0 },
+                LootEntry { pickup: Pickup::Ammo { weapon: None, amount: 24 }, weight: 1 },
+            ],
+            ..loot_table(1.0, 100)
+        };
+        let pickups = loot_table.roll(&mut rng);
+        assert_eq!(pickups.len(), 100);
+        assert!(pickups.iter().all(|pickup| matches!(pickup, Pickup::Ammo { weapon: None, amount: 24 })));
+    }
+
+    #[test]
+    fn table_without_entries_drops_nothing() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let loot_table = LootTable {
+            entries: vec![],
+            ..loot_table(1.0, 3)
+        };
+        assert!(loot_table.roll(&mut rng).is_empty());
+    }
+}
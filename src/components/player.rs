@@ -1,16 +1,16 @@
-use std::collections::VecDeque;
-use bevy::prelude::{Bundle, Component};
+use bevy::prelude::{Bundle, Component, Resource};
 use bevy_ecs_ldtk::LdtkIntCell;
 use bevy::core::Name;
 use bevy_xpbd_2d::components::{Collider, CollisionLayers, Position, RigidBody};
 use bevy::math::Vec2;
 use bevy::reflect::Reflect;
+use crate::components::effects::StatusEffects;
 use crate::components::general::Health;
-use crate::components::control::PlayerControl;
+use crate::components::control::{CycleDirection, PlayerControl};
 use crate::{Layer, METERS_PER_PIXEL};
 use crate::components::general::{CameraFollow, Prey};
 use crate::components::quad::QuadCoord;
-use crate::components::weapon::{CurrentWeapon, Weapon};
+use crate::components::weapon::{CurrentWeapon, Weapon, WeaponDef};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct PlayerStart;
@@ -23,9 +23,129 @@ pub struct PlayerStartBundle {
 #[derive(Component, Clone)]
 pub struct Player {}
 
-#[derive(Component, Clone, Reflect)]
+/// The player ran out of health, it no longer moves or shoots.
+#[derive(Component, Clone)]
+pub struct Dead {}
+
+#[derive(Resource, Clone)]
+pub struct PlayerSettings {
+    pub start_position: Vec2,
+}
+
+/// Weapons in stable slots, selected by number or cycled through in order.
+/// The equipped weapon lives in `CurrentWeapon` and its slot is empty until we switch away from it.
+#[derive(Component, Clone, Default, Reflect)]
 pub struct WeaponInventory {
-    pub weapons: VecDeque<Weapon>,
+    pub slots: Vec<Option<Weapon>>,
+    pub equipped: Option<usize>,
+}
+
+impl WeaponInventory {
+    pub fn new(weapons: impl IntoIterator<Item=Weapon>) -> Self {
+        Self {
+            slots: weapons.into_iter().map(Some).collect(),
+            equipped: None,
+        }
+    }
+
+    /// Updates the weapons we carry from reloaded definitions and adds any new ones.
+    pub fn update_from_defs(&mut self, current_weapon: &mut CurrentWeapon, weapon_defs: &[WeaponDef]) {
+        for weapon_def in weapon_defs {
+            if let Some(weapon) = self.find_weapon_mut(current_weapon, &weapon_def.name) {
+                weapon.update_from_def(weapon_def);
+            } else {
+                self.slots.push(Some(weapon_def.create_weapon()));
+            }
+        }
+    }
+
+    pub fn slot_of(&self, current_weapon: &CurrentWeapon, name: &str) -> Option<usize> {
+        match current_weapon.weapon.as_ref() {
+            Some(weapon) if weapon.name == name => self.equipped,
+            _ => self.slots.iter().position(|weapon| weapon.as_ref().is_some_and(|weapon| weapon.name == name)),
+        }
+    }
+
+    pub fn has_weapon(&self, current_weapon: &CurrentWeapon, name: &str) -> bool {
+        self.slot_of(current_weapon, name).is_some()
+    }
+
+    /// The weapon called `name`, whether it is equipped or carried.
+    pub fn find_weapon<'a>(&'a self, current_weapon: &'a CurrentWeapon, name: &str) -> Option<&'a Weapon> {
+        match current_weapon.weapon.as_ref() {
+            Some(weapon) if weapon.name == name => Some(weapon),
+            _ => self.slots.iter().flatten().find(|weapon| weapon.name == name),
+        }
+    }
+
+    pub fn find_weapon_mut<'a>(&'a mut self, current_weapon: &'a mut CurrentWeapon, name: &str) -> Option<&'a mut Weapon> {
+        match current_weapon.weapon.as_mut() {
+            Some(weapon) if weapon.name == name => Some(weapon),
+            _ => self.slots.iter_mut().flatten().find(|weapon| weapon.name == name),
+        }
+    }
+
+    /// Adds a new weapon in the next free slot, equipping it if we have nothing equipped.
+    /// If we already carry it, its ammo is topped up with what the new one comes with instead.
+    pub fn add_weapon(&mut self, current_weapon: &mut CurrentWeapon, weapon_def: &WeaponDef) {
+        if let Some(weapon) = self.find_weapon_mut(current_weapon, &weapon_def.name) {
+            weapon.add_ammo(weapon_def.ammo);
+            return;
+        }
+        self.slots.push(Some(weapon_def.create_weapon()));
+        if current_weapon.weapon.is_none() {
+            self.select_slot(current_weapon, self.slots.len() - 1);
+        }
+    }
+
+    /// Removes the weapon called `name`, the slots after it move down one.
+    /// Removing the equipped weapon leaves us with nothing equipped.
+    pub fn remove_weapon(&mut self, current_weapon: &mut CurrentWeapon, name: &str) -> Option<Weapon> {
+        let slot = self.slot_of(current_weapon, name)?;
+        let removed = self.slots.remove(slot);
+        match self.equipped {
+            Some(equipped) if equipped == slot => {
+                self.equipped = None;
+                let weapon = current_weapon.weapon.take();
+                *current_weapon = CurrentWeapon::default();
+                weapon
+            }
+            Some(equipped) if equipped > slot => {
+                self.equipped = Some(equipped - 1);
+                removed
+            }
+            _ => removed,
+        }
+    }
+
+    /// Puts the equipped weapon back in its slot and equips the one in `slot`.
+    /// Returns false if there is no such slot or it is already equipped.
+    pub fn select_slot(&mut self, current_weapon: &mut CurrentWeapon, slot: usize) -> bool {
+        if slot >= self.slots.len() || self.equipped == Some(slot) {
+            return false;
+        }
+        let Some(weapon) = self.slots[slot].take() else { return false; };
+        if let (Some(equipped), Some(equipped_weapon)) = (self.equipped, current_weapon.weapon.take()) {
+            self.slots[equipped] = Some(equipped_weapon);
+        }
+        current_weapon.set_weapon(weapon);
+        self.equipped = Some(slot);
+        true
+    }
+
+    pub fn cycle(&mut self, current_weapon: &mut CurrentWeapon, direction: &CycleDirection) -> bool {
+        let count = self.slots.len();
+        if count == 0 {
+            return false;
+        }
+        let slot = match (self.equipped, direction) {
+            (Some(equipped), CycleDirection::Forward) => (equipped + 1) % count,
+            (Some(equipped), CycleDirection::Backward) => (equipped + count - 1) % count,
+            (None, CycleDirection::Forward) => 0,
+            (None, CycleDirection::Backward) => count - 1,
+        };
+        self.select_slot(current_weapon, slot)
+    }
 }
 
 #[derive(Bundle, Clone)]
@@ -43,6 +163,7 @@ pub struct PlayerBundle {
     pub collision_layers: CollisionLayers,
     pub current_weapon: CurrentWeapon,
     pub weapon_inventory: WeaponInventory,
+    pub status_effects: StatusEffects,
 }
 
 impl Default for PlayerBundle {
@@ -61,14 +182,107 @@ impl Default for PlayerBundle {
                 y: 0.0,
             }),
             collider: Collider::cuboid(16.0 * METERS_PER_PIXEL, 8.0 * METERS_PER_PIXEL),
-            collision_layers: CollisionLayers::new([Layer::Player], [Layer::Walls, Layer::Water]),
-            current_weapon: CurrentWeapon {
-                weapon: None,
-                time_to_next_shot: 0.0,
-            },
-            weapon_inventory: WeaponInventory {
-                weapons: VecDeque::from(vec![])
-            },
+            collision_layers: CollisionLayers::new([Layer::Player], [Layer::Walls, Layer::Water, Layer::Pickup, Layer::Boid, Layer::Bullet]),
+            current_weapon: CurrentWeapon::default(),
+            weapon_inventory: WeaponInventory::default(),
+            status_effects: StatusEffects::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::weapon::WeaponDefs;
+
+    fn weapon_def(name: &str) -> WeaponDef {
+        WeaponDefs::default()
+            .defs
+            .into_iter()
+            .find(|weapon_def| weapon_def.name == name)
+            .expect("the shipped weapons should include it")
+    }
+
+    fn inventory(names: &[&str]) -> WeaponInventory {
+        WeaponInventory::new(names.iter().map(|name| weapon_def(name).create_weapon()))
+    }
+
+    fn equipped(current_weapon: &CurrentWeapon) -> Option<&str> {
+        current_weapon.weapon.as_ref().map(|weapon| weapon.name.as_str())
+    }
+
+    #[test]
+    fn selecting_puts_the_equipped_weapon_back_in_its_slot() {
+        let mut current_weapon = CurrentWeapon::default();
+        let mut weapon_inventory = inventory(&["Pistol", "Shotgun"]);
+        assert!(weapon_inventory.select_slot(&mut current_weapon, 0));
+        assert!(weapon_inventory.select_slot(&mut current_weapon, 1));
+        assert_eq!(equipped(&current_weapon), Some("Shotgun"));
+        assert_eq!(weapon_inventory.equipped, Some(1));
+        assert!(weapon_inventory.slots[0].is_some());
+        assert!(weapon_inventory.slots[1].is_none());
+
+        assert!(!weapon_inventory.select_slot(&mut current_weapon, 1));
+        assert!(!weapon_inventory.select_slot(&mut current_weapon, 2));
+    }
+
+    #[test]
+    fn cycling_wraps_around_both_ways() {
+        let mut current_weapon = CurrentWeapon::default();
+        let mut weapon_inventory = inventory(&["Pistol", "Shotgun", "Machete"]);
+        assert!(weapon_inventory.cycle(&mut current_weapon, &CycleDirection::Backward));
+        assert_eq!(equipped(&current_weapon), Some("Machete"));
+        assert!(weapon_inventory.cycle(&mut current_weapon, &CycleDirection::Forward));
+        assert_eq!(equipped(&current_weapon), Some("Pistol"));
+        assert!(weapon_inventory.cycle(&mut current_weapon, &CycleDirection::Backward));
+        assert_eq!(equipped(&current_weapon), Some("Machete"));
+
+        assert!(!WeaponInventory::default().cycle(&mut CurrentWeapon::default(), &CycleDirection::Forward));
+    }
+
+    #[test]
+    fn adding_a_weapon_we_carry_tops_up_its_ammo() {
+        let mut current_weapon = CurrentWeapon::default();
+        let mut weapon_inventory = inventory(&["Pistol"]);
+        weapon_inventory.select_slot(&mut current_weapon, 0);
+        let pistol = weapon_def("Pistol");
+        current_weapon.weapon.as_mut().unwrap().ammo_left = 0;
+
+        weapon_inventory.add_weapon(&mut current_weapon, &pistol);
+        assert_eq!(weapon_inventory.slots.len(), 1);
+        let weapon = current_weapon.weapon.as_ref().unwrap();
+        assert_eq!(weapon.ammo_left, pistol.ammo.min(weapon.max_ammo));
+    }
+
+    #[test]
+    fn adding_a_weapon_with_nothing_equipped_equips_it() {
+        let mut current_weapon = CurrentWeapon::default();
+        let mut weapon_inventory = WeaponInventory::default();
+        weapon_inventory.add_weapon(&mut current_weapon, &weapon_def("Shotgun"));
+        assert_eq!(equipped(&current_weapon), Some("Shotgun"));
+        assert_eq!(weapon_inventory.equipped, Some(0));
+
+        weapon_inventory.add_weapon(&mut current_weapon, &weapon_def("Pistol"));
+        assert_eq!(equipped(&current_weapon), Some("Shotgun"));
+        assert!(weapon_inventory.has_weapon(&current_weapon, "Pistol"));
+    }
+
+    #[test]
+    fn removing_weapons_keeps_the_equipped_slot_right() {
+        let mut current_weapon = CurrentWeapon::default();
+        let mut weapon_inventory = inventory(&["Pistol", "Shotgun", "Machete"]);
+        weapon_inventory.select_slot(&mut current_weapon, 2);
+
+        let removed = weapon_inventory.remove_weapon(&mut current_weapon, "Pistol");
+        assert_eq!(removed.map(|weapon| weapon.name), Some("Pistol".to_string()));
+        assert_eq!(weapon_inventory.equipped, Some(1));
+        assert_eq!(weapon_inventory.slot_of(&current_weapon, "Machete"), Some(1));
+
+        let removed = weapon_inventory.remove_weapon(&mut current_weapon, "Machete");
+        assert_eq!(removed.map(|weapon| weapon.name), Some("Machete".to_string()));
+        assert_eq!(weapon_inventory.equipped, None);
+        assert_eq!(equipped(&current_weapon), None);
+        assert_eq!(weapon_inventory.slots.len(), 1);
+        assert!(weapon_inventory.remove_weapon(&mut current_weapon, "Machete").is_none());
+    }
+}
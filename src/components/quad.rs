@@ -1,3 +1,4 @@
+use bevy::math::Vec2;
 use bevy::prelude::{Component, Entity, Reflect, Resource};
 use bevy::utils::{HashMap, HashSet};
 
@@ -12,7 +13,22 @@ impl QuadCoord {
     pub fn new(x: i32, y: i32) -> Self {
         Self { x, y }
     }
-    pub fn default() -> Self {
+
+    /// True for this quad and the eight quads around it.
+    pub fn is_adjacent(&self, other: &QuadCoord) -> bool {
+        (self.x - other.x).abs() <= 1 && (self.y - other.y).abs() <= 1
+    }
+
+    pub fn center(&self, quad_size: f32) -> Vec2 {
+        Vec2::new(
+            (self.x as f32 + 0.5) * quad_size,
+            (self.y as f32 + 0.5) * quad_size,
+        )
+    }
+}
+
+impl Default for QuadCoord {
+    fn default() -> Self {
         Self { x: -15000, y: -15000 }
     }
 }
@@ -23,9 +39,22 @@ pub enum Rebuild {
     GrowQuadSize,
 }
 
+/// Something wrong with the `QuadStore`, as found by `QuadStore::inconsistencies`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuadStoreInconsistency {
+    /// In the store, but no longer an entity with a `QuadCoord`.
+    Stray { entity: Entity, coord: QuadCoord },
+    /// Has a `QuadCoord`, but isn't in the store.
+    Missing { entity: Entity, coord: QuadCoord },
+    /// In the store, under another quad than its `QuadCoord` says.
+    Misplaced { entity: Entity, stored: QuadCoord, actual: QuadCoord },
+}
+
 #[derive(Resource)]
 pub struct QuadStore{
     pub entities: HashMap<QuadCoord, HashSet<Entity>>,
+    /// The quad every entity in `entities` is in, to find it again when it moves or is gone.
+    pub coords: HashMap<Entity, QuadCoord>,
     pub quad_size: f32,
     pub max_quad_size: f32,
     pub min_quad_size: f32,
@@ -34,3 +63,149 @@ pub struct QuadStore{
     pub largest_count: usize,
     pub rebuild_store: Rebuild,
 }
+
+impl QuadStore {
+    pub fn new(quad_size: f32, min_quad_size: f32, max_quad_size: f32, min_entities: usize, max_entities: usize) -> Self {
+        Self {
+            entities: HashMap::new(),
+            coords: HashMap::new(),
+            quad_size,
+            min_quad_size,
+            max_quad_size,
+            max_entities,
+            largest_count: 0,
+            min_entities,
+            rebuild_store: Rebuild::KeepQuadSize,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.coords.clear();
+        self.largest_count = 0;
+        self.rebuild_store = Rebuild::KeepQuadSize;
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.coords.contains_key(&entity)
+    }
+
+    /// Puts the entity in the quad at `coord`, taking it out of the one it was in.
+    pub fn insert(&mut self, entity: Entity, coord: QuadCoord) {
+        if self.coords.get(&entity) == Some(&coord) {
+            return;
+        }
+        self.remove(entity);
+        let set = self.entities.entry(coord).or_default();
+        set.insert(entity);
+        self.largest_count = self.largest_count.max(set.len());
+        self.coords.insert(entity, coord);
+    }
+
+    /// Takes the entity out of whatever quad it is in, dropping the quad if it was the last one in it.
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(coord) = self.coords.remove(&entity) else { return; };
+        if let Some(set) = self.entities.get_mut(&coord) {
+            set.remove(&entity);
+            if set.is_empty() {
+                self.entities.remove(&coord);
+            }
+        }
+    }
+
+    /// Compares the store to the entities that should be in it, every entity with a `QuadCoord`.
+    /// An empty list means the store is exactly what it should be.
+    pub fn inconsistencies(&self, members: impl IntoIterator<Item = (Entity, QuadCoord)>) -> Vec<QuadStoreInconsistency> {
+        let members = members.into_iter().collect::<HashMap<Entity, QuadCoord>>();
+        let mut inconsistencies = Vec::new();
+        for (entity, actual) in members.iter() {
+            if !self.coords.contains_key(entity) {
+                inconsistencies.push(QuadStoreInconsistency::Missing { entity: *entity, coord: *actual });
+            }
+        }
+        for (coord, set) in self.entities.iter() {
+            for entity in set.iter() {
+                match members.get(entity) {
+                    None => inconsistencies.push(QuadStoreInconsistency::Stray { entity: *entity, coord: *coord }),
+                    Some(actual) if actual != coord || self.coords.get(entity) != Some(coord) => {
+                        inconsistencies.push(QuadStoreInconsistency::Misplaced { entity: *entity, stored: *coord, actual: *actual });
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        inconsistencies
+    }
+
+    /// Every entity in the quads overlapping the square around `position`. Callers that care
+    /// about the actual distance still need to check it.
+    pub fn entities_within(&self, position: Vec2, radius: f32) -> Vec<Entity> {
+        let min = ((position - radius) / self.quad_size).floor();
+        let max = ((position + radius) / self.quad_size).floor();
+        (min.x as i32..=max.x as i32)
+            .flat_map(|x| (min.y as i32..=max.y as i32).map(move |y| QuadCoord::new(x, y)))
+            .filter_map(|coord| self.entities.get(&coord))
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> QuadStore {
+        QuadStore::new(10.0, 5.0, 40.0, 2, 8)
+    }
+
+    #[test]
+    fn insert_moves_and_remove_keep_the_store_consistent() {
+        let mut quad_store = store();
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        quad_store.insert(a, QuadCoord::new(0, 0));
+        quad_store.insert(b, QuadCoord::new(0, 0));
+        quad_store.insert(a, QuadCoord::new(1, 0));
+        assert!(quad_store.inconsistencies([(a, QuadCoord::new(1, 0)), (b, QuadCoord::new(0, 0))]).is_empty());
+        assert_eq!(quad_store.entities_within(Vec2::new(15.0, 5.0), 1.0), vec![a]);
+
+        quad_store.remove(b);
+        assert!(!quad_store.contains(b));
+        assert!(!quad_store.entities.contains_key(&QuadCoord::new(0, 0)));
+        assert!(quad_store.inconsistencies([(a, QuadCoord::new(1, 0))]).is_empty());
+    }
+
+    #[test]
+    fn despawned_entity_left_in_the_store_is_stray() {
+        let mut quad_store = store();
+        let (a, gone) = (Entity::from_raw(1), Entity::from_raw(2));
+        quad_store.insert(a, QuadCoord::new(0, 0));
+        quad_store.insert(gone, QuadCoord::new(2, 3));
+        assert_eq!(
+            quad_store.inconsistencies([(a, QuadCoord::new(0, 0))]),
+            vec![QuadStoreInconsistency::Stray { entity: gone, coord: QuadCoord::new(2, 3) }],
+        );
+    }
+
+    #[test]
+    fn entity_never_inserted_is_missing() {
+        let mut quad_store = store();
+        let (a, new) = (Entity::from_raw(1), Entity::from_raw(2));
+        quad_store.insert(a, QuadCoord::new(0, 0));
+        assert_eq!(
+            quad_store.inconsistencies([(a, QuadCoord::new(0, 0)), (new, QuadCoord::new(-1, 4))]),
+            vec![QuadStoreInconsistency::Missing { entity: new, coord: QuadCoord::new(-1, 4) }],
+        );
+    }
+
+    #[test]
+    fn entity_that_moved_without_the_store_knowing_is_misplaced() {
+        let mut quad_store = store();
+        let a = Entity::from_raw(1);
+        quad_store.insert(a, QuadCoord::new(0, 0));
+        assert_eq!(
+            quad_store.inconsistencies([(a, QuadCoord::new(0, 1))]),
+            vec![QuadStoreInconsistency::Misplaced { entity: a, stored: QuadCoord::new(0, 0), actual: QuadCoord::new(0, 1) }],
+        );
+    }
+}
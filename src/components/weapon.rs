@@ -1,46 +1,435 @@
+use std::fmt;
 use std::ops::Range;
+use bevy::asset::{AssetLoader, BoxedFuture, Handle, LoadContext, LoadedAsset};
 use bevy::core::Name;
 use bevy::prelude::{Bundle, Component, Entity, Reflect, Resource};
+use bevy::reflect::{TypePath, TypeUuid};
+use serde::Deserialize;
 use bevy_xpbd_2d::components::{Collider, CollisionLayers, LinearVelocity, RigidBody};
 use bevy_xpbd_2d::math::Vector2;
 use bevy_xpbd_2d::prelude::Position;
+use crate::components::effects::StatusEffect;
 
 #[derive(Component, Clone)]
 pub struct Projectile {}
 
-#[derive(Reflect, Clone)]
+/// Damage a projectile deals on hit, rolled from the firing weapon's damage range when it was fired.
+/// For explosives this is the damage at the centre of the blast.
+#[derive(Component, Clone, Debug)]
+pub struct ProjectileDamage {
+    pub amount: i32,
+    pub weapon: String,
+    /// Applied to whatever the projectile hits.
+    pub effects: Vec<StatusEffect>,
+    pub knockback: f32,
+}
+
+/// Makes a projectile explode instead of hitting a single boid.
+#[derive(Component, Clone, Debug)]
+pub struct Explosive {
+    pub radius: f32,
+    pub force: f32,
+}
+
+/// Projectiles are removed when they have flown `max_range` meters from `origin`, or when
+/// `time_left` runs out, whichever comes first. Explosives go off when that happens.
+#[derive(Component, Clone, Debug)]
+pub struct ProjectileLifetime {
+    pub origin: Vector2,
+    pub max_range: f32,
+    pub time_left: f32,
+}
+
+impl ProjectileLifetime {
+    pub fn is_spent(&self, position: Vector2) -> bool {
+        self.time_left <= 0.0 || position.distance(self.origin) > self.max_range
+    }
+}
+
+/// Lets a projectile fly on through `boids_left` more boids, keeping `damage_kept` of its damage
+/// every time it passes through one.
+#[derive(Component, Clone, Debug)]
+pub struct Piercing {
+    pub boids_left: i32,
+    pub damage_kept: f32,
+}
+
+/// Bounces a projectile off walls `bounces_left` more times.
+#[derive(Component, Clone, Debug)]
+pub struct Ricochet {
+    pub bounces_left: i32,
+}
+
+/// Grenades ignore boids, bounce off walls and detonate when their fuse runs out.
+#[derive(Component, Clone, Debug)]
+pub struct Fuse {
+    pub time_left: f32,
+}
+
+#[derive(Reflect, Clone, Debug, Default, PartialEq, Deserialize)]
 pub enum WeaponType {
+    #[default]
     Projectile,
     Melee,
 }
 
-#[derive(Reflect, Clone)]
+/// Automatic weapons keep firing while the trigger is held, semi-automatic ones
+/// fire once (or one burst) per pull.
+#[derive(Reflect, Clone, Debug, Default, PartialEq, Deserialize)]
+pub enum FireMode {
+    #[default]
+    Auto,
+    Semi,
+}
+
+/// Changes a weapon's stats. Modifiers stack, percentages add up before they are applied,
+/// so two +50% rate of fire modifiers double it.
+#[derive(Reflect, Clone, Debug, PartialEq, Deserialize)]
+pub enum WeaponModifier {
+    RateOfFire(f32),
+    ReloadSpeed(f32),
+    BulletSpeed(f32),
+    Spread(f32),
+    Damage(i32),
+    Pellets(i32),
+    MagazineSize(i32),
+    Piercing(i32),
+    Bounces(i32),
+}
+
+#[derive(Reflect, Clone, Deserialize)]
 pub enum AmmoType {
     Bullet(String),
     Rocket(String),
     Grenade(String),
 }
 
+impl Default for AmmoType {
+    fn default() -> Self {
+        AmmoType::Bullet("Bullet".to_string())
+    }
+}
+
 //Should perhaps be some kind of macro I guess?
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
 pub struct WeaponDef {
     pub name: String,
+    #[serde(default)]
+    pub weapon_type: WeaponType,
     pub damage: Range<i32>,
+    #[serde(default)]
     pub bullet_speed: f32,
+    #[serde(default)]
     pub ammo: i32,
+    #[serde(default)]
     pub rof: f32,
+    #[serde(default)]
     pub ammo_type: AmmoType,
+    /// Rockets and grenades damage every boid within this radius, falling off with distance.
+    #[serde(default)]
+    pub explosion_radius: f32,
+    #[serde(default)]
+    pub explosion_force: f32,
+    /// Seconds a grenade flies before it detonates.
+    #[serde(default)]
+    pub fuse: f32,
+    /// Melee weapons hit every boid within `melee_range` and `melee_arc` degrees of where we aim,
+    /// once every `swing_time` seconds, stunning them for `stagger` seconds.
+    #[serde(default)]
+    pub melee_range: f32,
+    #[serde(default)]
+    pub melee_arc: f32,
+    #[serde(default)]
+    pub swing_time: f32,
+    #[serde(default)]
+    pub stagger: f32,
+    /// Speed in meters per second a hit knocks a boid back with, along the direction it was hit from.
+    #[serde(default)]
+    pub knockback: f32,
+    /// Rounds per magazine, 0 means the weapon feeds straight from its ammo.
+    #[serde(default)]
+    pub magazine_size: i32,
+    #[serde(default)]
+    pub reload_time: f32,
+    /// Meters and seconds a projectile flies before it is removed.
+    #[serde(default = "default_max_range")]
+    pub max_range: f32,
+    #[serde(default = "default_lifetime")]
+    pub lifetime: f32,
+    #[serde(default)]
+    pub fire_mode: FireMode,
+    /// Every projectile leaves at a random angle within a cone `spread` degrees wide.
+    #[serde(default)]
+    pub spread: f32,
+    /// Projectiles per shot, all from the same round.
+    #[serde(default = "default_count")]
+    pub pellets: i32,
+    /// Shots per pull of the trigger, `burst_delay` seconds apart.
+    #[serde(default = "default_count")]
+    pub burst: i32,
+    #[serde(default)]
+    pub burst_delay: f32,
+    /// Boids a projectile passes through before it stops, keeping `pierce_damage` of its
+    /// damage each time.
+    #[serde(default)]
+    pub piercing: i32,
+    #[serde(default = "default_pierce_damage")]
+    pub pierce_damage: f32,
+    /// Times a projectile bounces off walls before it stops.
+    #[serde(default)]
+    pub bounces: i32,
+    #[serde(default)]
+    pub modifiers: Vec<WeaponModifier>,
+    /// Status effects applied to whatever the weapon hits, on top of any `stagger`.
+    #[serde(default)]
+    pub effects: Vec<StatusEffect>,
+}
+
+fn default_max_range() -> f32 {
+    100.0
+}
+
+fn default_lifetime() -> f32 {
+    5.0
+}
+
+fn default_count() -> i32 {
+    1
+}
+
+fn default_pierce_damage() -> f32 {
+    0.5
 }
 
 impl WeaponDef {
     pub fn create_weapon(&self) -> Weapon {
-        Weapon::new(&self)
+        Weapon::new(self)
+    }
+
+    /// Returns the offending field and what is wrong with it.
+    pub fn validate(&self) -> Result<(), (&'static str, String)> {
+        if self.name.trim().is_empty() {
+            return Err(("name", "must not be empty".to_string()));
+        }
+        if self.damage.is_empty() {
+            return Err(("damage", format!("range {}..{} is empty", self.damage.start, self.damage.end)));
+        }
+        for effect in self.effects.iter() {
+            effect.validate().map_err(|error| ("effects", error))?;
+        }
+        if self.knockback < 0.0 {
+            return Err(("knockback", format!("must not be negative, was {}", self.knockback)));
+        }
+        if self.weapon_type == WeaponType::Melee {
+            if self.melee_range <= 0.0 {
+                return Err(("melee_range", format!("must be above zero for melee weapons, was {}", self.melee_range)));
+            }
+            if self.melee_arc <= 0.0 || self.melee_arc > 360.0 {
+                return Err(("melee_arc", format!("must be between 0 and 360 degrees, was {}", self.melee_arc)));
+            }
+            if self.swing_time <= 0.0 {
+                return Err(("swing_time", format!("must be above zero for melee weapons, was {}", self.swing_time)));
+            }
+            return Ok(());
+        }
+        if self.bullet_speed <= 0.0 {
+            return Err(("bullet_speed", format!("must be above zero, was {}", self.bullet_speed)));
+        }
+        if self.ammo <= 0 {
+            return Err(("ammo", format!("must be above zero, was {}", self.ammo)));
+        }
+        if self.rof <= 0.0 {
+            return Err(("rof", format!("must be above zero, was {}", self.rof)));
+        }
+        if self.max_range <= 0.0 {
+            return Err(("max_range", format!("must be above zero, was {}", self.max_range)));
+        }
+        if self.lifetime <= 0.0 {
+            return Err(("lifetime", format!("must be above zero, was {}", self.lifetime)));
+        }
+        if self.spread < 0.0 || self.spread > 360.0 {
+            return Err(("spread", format!("must be between 0 and 360 degrees, was {}", self.spread)));
+        }
+        if self.pellets < 1 {
+            return Err(("pellets", format!("must be at least one, was {}", self.pellets)));
+        }
+        if self.burst < 1 {
+            return Err(("burst", format!("must be at least one, was {}", self.burst)));
+        }
+        if self.burst > 1 && self.burst_delay <= 0.0 {
+            return Err(("burst_delay", format!("must be above zero for bursts, was {}", self.burst_delay)));
+        }
+        if self.piercing < 0 {
+            return Err(("piercing", format!("must not be negative, was {}", self.piercing)));
+        }
+        if self.pierce_damage <= 0.0 || self.pierce_damage > 1.0 {
+            return Err(("pierce_damage", format!("must be above zero and at most one, was {}", self.pierce_damage)));
+        }
+        if self.bounces < 0 {
+            return Err(("bounces", format!("must not be negative, was {}", self.bounces)));
+        }
+        if self.magazine_size < 0 {
+            return Err(("magazine_size", format!("must not be negative, was {}", self.magazine_size)));
+        }
+        if self.magazine_size > 0 && self.reload_time <= 0.0 {
+            return Err(("reload_time", format!("must be above zero for weapons with a magazine, was {}", self.reload_time)));
+        }
+        if matches!(self.ammo_type, AmmoType::Rocket(_) | AmmoType::Grenade(_)) && self.explosion_radius <= 0.0 {
+            return Err(("explosion_radius", format!("must be above zero for explosive ammo, was {}", self.explosion_radius)));
+        }
+        if matches!(self.ammo_type, AmmoType::Grenade(_)) && self.fuse <= 0.0 {
+            return Err(("fuse", format!("must be above zero for grenades, was {}", self.fuse)));
+        }
+        Ok(())
     }
 }
 
 impl Weapon {
     pub fn rof_to_cooldown(&self) -> f32 {
-        1.0 / self.rof
+        let rate = self.percent_modifier(|modifier| match modifier {
+            WeaponModifier::RateOfFire(percent) => Some(*percent),
+            _ => None,
+        });
+        match self.weapon_type {
+            WeaponType::Projectile => 1.0 / (self.rof * rate),
+            WeaponType::Melee => self.swing_time / rate,
+        }
+    }
+
+    /// Multiplier from the percentages the modifiers `select` picks, never below a tenth.
+    fn percent_modifier(&self, select: impl Fn(&WeaponModifier) -> Option<f32>) -> f32 {
+        (1.0 + self.modifiers.iter().filter_map(select).sum::<f32>() / 100.0).max(0.1)
+    }
+
+    fn flat_modifier(&self, select: impl Fn(&WeaponModifier) -> Option<i32>) -> i32 {
+        self.modifiers.iter().filter_map(select).sum()
+    }
+
+    /// Everything a hit with this weapon does to the target besides damage.
+    pub fn hit_effects(&self) -> Vec<StatusEffect> {
+        let mut effects = self.effects.clone();
+        if self.stagger > 0.0 {
+            effects.push(StatusEffect::stun(self.stagger));
+        }
+        effects
+    }
+
+    pub fn effective_damage(&self) -> Range<i32> {
+        let bonus = self.flat_modifier(|modifier| match modifier {
+            WeaponModifier::Damage(damage) => Some(*damage),
+            _ => None,
+        });
+        let start = (self.damage.start + bonus).max(0);
+        start..(self.damage.end + bonus).max(start + 1)
+    }
+
+    pub fn effective_bullet_speed(&self) -> f32 {
+        self.bullet_speed * self.percent_modifier(|modifier| match modifier {
+            WeaponModifier::BulletSpeed(percent) => Some(*percent),
+            _ => None,
+        })
+    }
+
+    pub fn effective_spread(&self) -> f32 {
+        (self.spread * self.percent_modifier(|modifier| match modifier {
+            WeaponModifier::Spread(percent) => Some(*percent),
+            _ => None,
+        })).min(360.0)
+    }
+
+    pub fn effective_reload_time(&self) -> f32 {
+        self.reload_time / self.percent_modifier(|modifier| match modifier {
+            WeaponModifier::ReloadSpeed(percent) => Some(*percent),
+            _ => None,
+        })
+    }
+
+    pub fn effective_pellets(&self) -> i32 {
+        (self.pellets + self.flat_modifier(|modifier| match modifier {
+            WeaponModifier::Pellets(pellets) => Some(*pellets),
+            _ => None,
+        })).max(1)
+    }
+
+    /// Weapons without a magazine don't get one from modifiers.
+    pub fn effective_magazine_size(&self) -> i32 {
+        if self.magazine_size <= 0 {
+            return 0;
+        }
+        (self.magazine_size + self.flat_modifier(|modifier| match modifier {
+            WeaponModifier::MagazineSize(rounds) => Some(*rounds),
+            _ => None,
+        })).max(1)
+    }
+
+    pub fn effective_piercing(&self) -> i32 {
+        (self.piercing + self.flat_modifier(|modifier| match modifier {
+            WeaponModifier::Piercing(boids) => Some(*boids),
+            _ => None,
+        })).max(0)
+    }
+
+    pub fn effective_bounces(&self) -> i32 {
+        (self.bounces + self.flat_modifier(|modifier| match modifier {
+            WeaponModifier::Bounces(bounces) => Some(*bounces),
+            _ => None,
+        })).max(0)
+    }
+
+    pub fn add_modifier(&mut self, modifier: WeaponModifier) {
+        self.modifiers.push(modifier);
+        self.clamp_magazine();
+    }
+
+    pub fn remove_modifier(&mut self, modifier: &WeaponModifier) -> bool {
+        let Some(index) = self.modifiers.iter().position(|attached| attached == modifier) else { return false; };
+        self.modifiers.remove(index);
+        self.clamp_magazine();
+        true
+    }
+
+    /// Puts rounds that no longer fit in the magazine back with the rest of the ammo.
+    fn clamp_magazine(&mut self) {
+        let spilled = (self.current_ammo - self.effective_magazine_size()).max(0);
+        self.current_ammo -= spilled;
+        self.ammo_left += spilled;
+    }
+
+    pub fn is_melee(&self) -> bool {
+        self.weapon_type == WeaponType::Melee
+    }
+
+    pub fn has_magazine(&self) -> bool {
+        self.effective_magazine_size() > 0
+    }
+
+    /// Rounds we can fire without reloading.
+    pub fn rounds_ready(&self) -> i32 {
+        if self.has_magazine() {
+            self.current_ammo
+        } else {
+            self.ammo_left
+        }
+    }
+
+    pub fn can_reload(&self) -> bool {
+        self.has_magazine() && self.current_ammo < self.effective_magazine_size() && self.ammo_left > 0
+    }
+
+    pub fn is_out_of_ammo(&self) -> bool {
+        !self.is_melee() && self.rounds_ready() <= 0 && (!self.has_magazine() || self.ammo_left <= 0)
+    }
+
+    /// Adds ammo to `ammo_left`, never above `max_ammo`.
+    pub fn add_ammo(&mut self, amount: i32) {
+        self.ammo_left = (self.ammo_left + amount).min(self.max_ammo).max(self.ammo_left);
+    }
+
+    /// Moves as many rounds as fit from `ammo_left` into the magazine.
+    pub fn reload(&mut self) {
+        let rounds = (self.effective_magazine_size() - self.current_ammo).min(self.ammo_left).max(0);
+        self.current_ammo += rounds;
+        self.ammo_left -= rounds;
     }
 }
 
@@ -51,65 +440,202 @@ impl Weapon {
 pub struct Weapon {
     pub ammo_left: i32,
     pub name: String,
+    pub weapon_type: WeaponType,
     pub damage: Range<i32>,
     pub bullet_speed: f32,
     pub current_ammo: i32,
     pub max_ammo: i32,
     pub rof: f32,
     pub ammo_type: AmmoType,
+    pub explosion_radius: f32,
+    pub explosion_force: f32,
+    pub fuse: f32,
+    pub melee_range: f32,
+    pub melee_arc: f32,
+    pub swing_time: f32,
+    pub stagger: f32,
+    pub knockback: f32,
+    pub magazine_size: i32,
+    pub reload_time: f32,
+    pub max_range: f32,
+    pub lifetime: f32,
+    pub fire_mode: FireMode,
+    pub spread: f32,
+    pub pellets: i32,
+    pub burst: i32,
+    pub burst_delay: f32,
+    pub piercing: i32,
+    pub pierce_damage: f32,
+    pub bounces: i32,
+    pub modifiers: Vec<WeaponModifier>,
+    pub effects: Vec<StatusEffect>,
 }
 
 
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct WeaponDefs {
     pub defs: Vec<WeaponDef>,
 }
 
+/// The weapons the game ships with, used until the asset at `WeaponsPlugin::path` has loaded.
 impl Default for WeaponDefs {
     fn default() -> Self {
+        let weapon_defs = ron::de::from_str::<WeaponDefsAsset>(include_str!("../../assets/weapons/weapons.weapons.ron"))
+            .expect("assets/weapons/weapons.weapons.ron should be valid");
         Self {
-            defs: vec![
-                WeaponDef {
-                    name: "Pistol".to_string(),
-                    damage: 1..2,
-                    ammo: 1000,
-                    bullet_speed: 100.0,
-                    rof: 2.0,
-                    ammo_type: AmmoType::Bullet("Bullet".to_string()),
-                },
-                WeaponDef {
-                    name: "Rocket Launcher".to_string(),
-                    damage: 10..20,
-                    ammo: 3000,
-                    bullet_speed: 25.0,
-                    rof: 1.0,
-                    ammo_type: AmmoType::Rocket("Rocket".to_string()),
-                },
-                WeaponDef {
-                    name: "Grenade Launcher".to_string(),
-                    damage: 10..20,
-                    ammo: 3000,
-                    bullet_speed: 12.0,
-                    rof: 1.0,
-                    ammo_type: AmmoType::Grenade("Grenade".to_string()),
-                },
-            ],
+            defs: weapon_defs.weapons,
         }
     }
 }
 
+/*
+Weapons are defined in a `.weapons.ron` asset so they can be tuned without a rebuild,
+and are hot reloaded into WeaponDefs when the file changes:
+
+(
+    weapons: [
+        (
+            name: "Pistol",
+            damage: (start: 1, end: 2),
+            bullet_speed: 100.0,
+            ammo: 1000,
+            rof: 2.0,
+            ammo_type: Bullet("Bullet"),
+        ),
+    ],
+)
+ */
+#[derive(Clone, Deserialize, TypeUuid, TypePath)]
+#[uuid = "0d6c3e4a-2b8f-4f7e-a1c5-93e2d8b7f640"]
+pub struct WeaponDefsAsset {
+    pub weapons: Vec<WeaponDef>,
+}
+
+#[derive(Resource)]
+pub struct WeaponDefsFile {
+    pub path: String,
+    pub handle: Handle<WeaponDefsAsset>,
+}
+
+#[derive(Debug)]
+pub struct WeaponDefError {
+    pub path: String,
+    pub weapon: String,
+    pub field: &'static str,
+    pub problem: String,
+}
+
+impl fmt::Display for WeaponDefError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: weapon \"{}\", field `{}`: {}", self.path, self.weapon, self.field, self.problem)
+    }
+}
+
+impl std::error::Error for WeaponDefError {}
+
+#[derive(Default)]
+pub struct WeaponDefsLoader;
+
+impl AssetLoader for WeaponDefsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let path = load_context.path().display().to_string();
+            let weapon_defs = ron::de::from_bytes::<WeaponDefsAsset>(bytes)
+                .map_err(|error| bevy::asset::Error::msg(format!("{}: {}", path, error)))?;
+            for weapon_def in weapon_defs.weapons.iter() {
+                weapon_def.validate().map_err(|(field, problem)| WeaponDefError {
+                    path: path.clone(),
+                    weapon: weapon_def.name.clone(),
+                    field,
+                    problem,
+                })?;
+            }
+            load_context.set_default_asset(LoadedAsset::new(weapon_defs));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["weapons.ron"]
+    }
+}
+
 impl Weapon {
+    /// Picks up changed stats from a reloaded definition, keeping the ammo we have left.
+    pub fn update_from_def(&mut self, weapon_def: &WeaponDef) {
+        self.damage = weapon_def.damage.clone();
+        self.bullet_speed = weapon_def.bullet_speed;
+        self.rof = weapon_def.rof;
+        self.ammo_type = weapon_def.ammo_type.clone();
+        self.explosion_radius = weapon_def.explosion_radius;
+        self.explosion_force = weapon_def.explosion_force;
+        self.fuse = weapon_def.fuse;
+        self.weapon_type = weapon_def.weapon_type.clone();
+        self.melee_range = weapon_def.melee_range;
+        self.melee_arc = weapon_def.melee_arc;
+        self.swing_time = weapon_def.swing_time;
+        self.stagger = weapon_def.stagger;
+        self.knockback = weapon_def.knockback;
+        self.max_ammo = weapon_def.ammo;
+        self.magazine_size = weapon_def.magazine_size;
+        self.reload_time = weapon_def.reload_time;
+        self.max_range = weapon_def.max_range;
+        self.lifetime = weapon_def.lifetime;
+        self.fire_mode = weapon_def.fire_mode.clone();
+        self.spread = weapon_def.spread;
+        self.pellets = weapon_def.pellets;
+        self.burst = weapon_def.burst;
+        self.burst_delay = weapon_def.burst_delay;
+        self.piercing = weapon_def.piercing;
+        self.pierce_damage = weapon_def.pierce_damage;
+        self.bounces = weapon_def.bounces;
+        self.modifiers = weapon_def.modifiers.clone();
+        self.effects = weapon_def.effects.clone();
+        self.clamp_magazine();
+        self.ammo_left = self.ammo_left.min(self.max_ammo);
+    }
+
     pub fn new(weapon_def: &WeaponDef) -> Self {
-        Self {
-            ammo_left: weapon_def.ammo.clone(),
+        let mut weapon = Self {
+            ammo_left: weapon_def.ammo,
             name: weapon_def.name.clone(),
             damage: weapon_def.damage.clone(),
-            bullet_speed: weapon_def.bullet_speed.clone(),
-            current_ammo: weapon_def.ammo.clone(),
-            rof: weapon_def.rof.clone(),
+            bullet_speed: weapon_def.bullet_speed,
+            current_ammo: 0,
+            rof: weapon_def.rof,
             ammo_type: weapon_def.ammo_type.clone(),
-            max_ammo: weapon_def.ammo.clone(),
-        }
+            max_ammo: weapon_def.ammo,
+            explosion_radius: weapon_def.explosion_radius,
+            explosion_force: weapon_def.explosion_force,
+            fuse: weapon_def.fuse,
+            weapon_type: weapon_def.weapon_type.clone(),
+            melee_range: weapon_def.melee_range,
+            melee_arc: weapon_def.melee_arc,
+            swing_time: weapon_def.swing_time,
+            stagger: weapon_def.stagger,
+            knockback: weapon_def.knockback,
+            magazine_size: weapon_def.magazine_size,
+            reload_time: weapon_def.reload_time,
+            max_range: weapon_def.max_range,
+            lifetime: weapon_def.lifetime,
+            fire_mode: weapon_def.fire_mode.clone(),
+            spread: weapon_def.spread,
+            pellets: weapon_def.pellets,
+            burst: weapon_def.burst,
+            burst_delay: weapon_def.burst_delay,
+            piercing: weapon_def.piercing,
+            pierce_damage: weapon_def.pierce_damage,
+            bounces: weapon_def.bounces,
+            modifiers: weapon_def.modifiers.clone(),
+            effects: weapon_def.effects.clone(),
+        };
+        // Weapons come with a full magazine, if they have one
+        weapon.reload();
+        weapon
     }
 }
 
@@ -117,28 +643,112 @@ impl Weapon {
 pub struct CurrentWeapon {
     pub weapon: Option<Weapon>,
     pub time_to_next_shot: f32,
+    /// Set while reloading, we cannot fire until it runs out.
+    pub reload_time_left: Option<f32>,
+    /// Shots left of the burst we are firing.
+    pub burst_left: i32,
+    /// Semi-automatic weapons need the trigger released before they fire again.
+    pub trigger_released: bool,
 }
 
 impl CurrentWeapon {
     pub fn set_weapon(&mut self, weapon: Weapon) {
         self.time_to_next_shot = weapon.rof_to_cooldown();
+        self.reload_time_left = None;
+        self.burst_left = 0;
         self.weapon = Some(weapon);
     }
 
+    pub fn release_trigger(&mut self) {
+        self.trigger_released = true;
+    }
+
+    pub fn is_bursting(&self) -> bool {
+        self.burst_left > 0
+    }
+
+    fn trigger_ready(&self) -> bool {
+        self.is_bursting()
+            || self.trigger_released
+            || self.weapon.as_ref().is_some_and(|weapon| weapon.fire_mode == FireMode::Auto)
+    }
+
     pub fn tick(&mut self, delta: f32) {
         self.time_to_next_shot -= delta;
     }
 
     pub fn can_fire(&self) -> bool {
-        self.weapon.is_some() && self.time_to_next_shot <= 0.0 && self.weapon.as_ref().unwrap().ammo_left > 0
+        self.weapon.as_ref().is_some_and(|weapon| weapon.is_melee() || weapon.rounds_ready() > 0)
+            && self.time_to_next_shot <= 0.0
+            && !self.is_reloading()
+            && self.trigger_ready()
     }
     pub fn fire(&mut self) {
         if let Some(weapon) = self.weapon.as_mut() {
-            weapon.ammo_left -= 1;
-            self.time_to_next_shot = weapon.rof_to_cooldown();
+            if weapon.has_magazine() {
+                weapon.current_ammo -= 1;
+            } else if !weapon.is_melee() {
+                weapon.ammo_left -= 1;
+            }
+            if self.burst_left > 0 {
+                self.burst_left -= 1;
+            } else {
+                self.burst_left = weapon.burst - 1;
+                self.trigger_released = false;
+            }
+            self.time_to_next_shot = if self.burst_left > 0 {
+                weapon.burst_delay
+            } else {
+                weapon.rof_to_cooldown()
+            };
         }
     }
 
+    pub fn is_reloading(&self) -> bool {
+        self.reload_time_left.is_some()
+    }
+
+    /// Empty magazine but rounds left to put in it.
+    pub fn needs_reload(&self) -> bool {
+        !self.is_reloading() && self.weapon.as_ref().is_some_and(|weapon| weapon.has_magazine() && weapon.current_ammo <= 0 && weapon.ammo_left > 0)
+    }
+
+    pub fn start_reload(&mut self) -> bool {
+        if self.is_reloading() {
+            return false;
+        }
+        match self.weapon.as_ref() {
+            Some(weapon) if weapon.can_reload() => {
+                self.reload_time_left = Some(weapon.effective_reload_time());
+                self.burst_left = 0;
+                true
+            }
+            _ => false
+        }
+    }
+
+    /// Returns true when the reload finished during this tick.
+    pub fn tick_reload(&mut self, delta: f32) -> bool {
+        let Some(time_left) = self.reload_time_left.as_mut() else { return false; };
+        *time_left -= delta;
+        if *time_left > 0.0 {
+            return false;
+        }
+        self.reload_time_left = None;
+        if let Some(weapon) = self.weapon.as_mut() {
+            weapon.reload();
+        }
+        true
+    }
+
+    pub fn is_out_of_ammo(&self) -> bool {
+        self.weapon.as_ref().is_some_and(|weapon| weapon.is_out_of_ammo())
+    }
+
+    pub fn is_melee(&self) -> bool {
+        self.weapon.as_ref().is_some_and(|weapon| weapon.is_melee())
+    }
+
     pub fn did_we_fire(&mut self) -> bool {
         self.can_fire() && {
             self.fire();
@@ -147,7 +757,7 @@ impl CurrentWeapon {
     }
 
     pub fn bullet_speed_or_zero(&self) -> f32 {
-        self.weapon.as_ref().map(|w| w.bullet_speed).unwrap_or(0.0)
+        self.weapon.as_ref().map(|w| w.effective_bullet_speed()).unwrap_or(0.0)
     }
 }
 
@@ -156,12 +766,34 @@ impl Default for CurrentWeapon {
         Self {
             weapon: None,
             time_to_next_shot: 0.0,
+            reload_time_left: None,
+            burst_left: 0,
+            trigger_released: true,
         }
     }
 }
 
 #[derive(Component)]
-pub struct Shooter(Entity);
+pub struct Shooter(pub Entity);
+
+/// Seconds after switching weapons before the new one can be used.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct WeaponSwitchSettings {
+    pub delay: f32,
+}
+
+/// Who the projectiles fired by players may hurt. Nobody is ever hit by their own projectiles,
+/// other players only when friendly fire is enabled.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct FriendlyFire {
+    pub enabled: bool,
+}
+
+impl FriendlyFire {
+    pub fn allows(&self, shooter: Entity, target: Entity, shooter_is_player: bool) -> bool {
+        shooter != target && (self.enabled || !shooter_is_player)
+    }
+}
 
 #[derive(Bundle)]
 pub struct ProjectileBundle {
@@ -172,6 +804,8 @@ pub struct ProjectileBundle {
     collider: Collider,
     collision_layers: CollisionLayers,
     shooter: Shooter,
+    damage: ProjectileDamage,
+    lifetime: ProjectileLifetime,
     linear_velocity: LinearVelocity,
 }
 
@@ -182,16 +816,33 @@ impl ProjectileBundle {
         lv: Vector2,
         collider: Collider,
         collision_layers: CollisionLayers,
-        shooter: Entity) -> Self {
+        shooter: Entity,
+        damage: ProjectileDamage,
+        lifetime: ProjectileLifetime) -> Self {
         Self {
             name: Name::from(name),
             projectile: Projectile {},
-            position: from.clone(),
+            position: from,
             rigid_body: RigidBody::Kinematic,
             collider,
             collision_layers,
             shooter: Shooter(shooter),
+            damage,
+            lifetime,
             linear_velocity: LinearVelocity(lv),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_weapon_defs_are_the_shipped_asset() {
+        let weapon_defs = WeaponDefs::default();
+        assert!(weapon_defs.defs.iter().any(|weapon_def| weapon_def.name == "Pistol"));
+        for weapon_def in weapon_defs.defs.iter() {
+            assert_eq!(weapon_def.validate(), Ok(()), "{}", weapon_def.name);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::{Entity, Event};
+
+#[derive(Event)]
+pub struct ReloadStartedEvent {
+    pub shooter: Entity,
+    pub weapon: String,
+}
+
+#[derive(Event)]
+pub struct ReloadFinishedEvent {
+    pub shooter: Entity,
+    pub weapon: String,
+}
+
+/// Sent when a shot leaves the weapon with nothing in the magazine and nothing to reload with.
+#[derive(Event)]
+pub struct OutOfAmmoEvent {
+    pub shooter: Entity,
+    pub weapon: String,
+}
@@ -0,0 +1,33 @@
+use bevy::math::Vec2;
+use bevy::prelude::{Entity, Event};
+
+#[derive(Event)]
+pub struct DamageEvent {
+    pub shooter: Entity,
+    pub target: Entity,
+    pub amount: i32,
+    pub weapon: String,
+}
+
+/// A boid biting the player.
+#[derive(Event)]
+pub struct PlayerDamagedEvent {
+    pub player: Entity,
+    pub boid: Entity,
+    pub amount: i32,
+}
+
+/// `killer` dealt the killing blow, with `weapon`, which is the name of the status effect
+/// for boids that burnt or bled to death.
+#[derive(Event)]
+pub struct BoidDiedEvent {
+    pub boid: Entity,
+    pub killer: Entity,
+    pub weapon: String,
+    pub position: Vec2,
+}
+
+#[derive(Event)]
+pub struct PlayerDiedEvent {
+    pub player: Entity,
+}
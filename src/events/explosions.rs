@@ -0,0 +1,30 @@
+use bevy::math::Vec2;
+use bevy::prelude::{Entity, Event};
+use crate::components::effects::StatusEffect;
+use crate::components::weapon::{Explosive, ProjectileDamage};
+
+#[derive(Event)]
+pub struct ExplosionEvent {
+    pub position: Vec2,
+    pub radius: f32,
+    pub damage: i32,
+    pub force: f32,
+    pub shooter: Entity,
+    pub weapon: String,
+    pub effects: Vec<StatusEffect>,
+}
+
+impl ExplosionEvent {
+    /// The explosion of a rocket or grenade going off at `position`.
+    pub fn from_projectile(position: Vec2, explosive: &Explosive, damage: &ProjectileDamage, shooter: Entity) -> Self {
+        Self {
+            position,
+            radius: explosive.radius,
+            damage: damage.amount,
+            force: explosive.force,
+            shooter,
+            weapon: damage.weapon.clone(),
+            effects: damage.effects.clone(),
+        }
+    }
+}
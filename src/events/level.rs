@@ -0,0 +1,7 @@
+use bevy::prelude::Event;
+
+/// Switches to the level with this identifier in the loaded LDtk project.
+#[derive(Event)]
+pub struct ChangeLevelEvent {
+    pub identifier: String,
+}
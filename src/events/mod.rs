@@ -1 +1,6 @@
-pub(crate) mod collisions;
\ No newline at end of file
+pub mod ammo;
+pub mod collisions;
+pub mod damage;
+pub mod explosions;
+pub mod level;
+pub mod weapons;
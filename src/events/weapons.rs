@@ -0,0 +1,8 @@
+use bevy::prelude::{Entity, Event};
+
+#[derive(Event)]
+pub struct WeaponSwitchedEvent {
+    pub shooter: Entity,
+    pub from: Option<String>,
+    pub to: String,
+}
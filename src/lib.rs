@@ -0,0 +1,38 @@
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::States;
+use bevy_xpbd_2d::prelude::PhysicsLayer;
+
+pub mod components;
+pub mod systems;
+pub mod boids;
+pub mod events;
+pub mod plugins;
+
+pub const PIXELS_PER_METER: f32 = 16.0;
+pub const METERS_PER_PIXEL: f32 = 1.0 / PIXELS_PER_METER;
+pub const CAMERA_SCALE: f32 = 0.75;
+pub const FIXED_TIME_STEP: f32 = 1.0 / 10.0;
+
+#[derive(PhysicsLayer)]
+pub enum Layer {
+    Player,
+    Boid,
+    Walls,
+    Water,
+    Bullet,
+    Pickup,
+}
+
+#[derive(States, Default, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+/// Runs whenever a new game starts, from the main menu or after a game over.
+/// Plugins clear out and spawn what they own in it.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NewGame;
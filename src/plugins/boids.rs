@@ -0,0 +1,112 @@
+use bevy::prelude::{AddAsset, App, FixedUpdate, Handle, in_state, IntoSystemConfigs, IntoSystemSetConfig, Plugin, PreUpdate, Startup, Update};
+use big_brain::{BigBrainPlugin, BigBrainSet};
+use crate::boids::ai::{attack_and_eat_action_system, find_prey_action_system, Hunger, hunger_scorer_system, hunger_system, hunt_prey_action_system, HuntTarget, wander_action_system, wander_to_quadrant_action_system};
+use crate::boids::brain::{BoidBrainDefs, BoidBrains, BoidBrainsLoader};
+use crate::boids::components::{BoidDirection, BoidStuff, Knockback};
+use crate::boids::resources::{BoidGenerationSettings, CorpseSettings};
+use crate::boids::systems::{attach_boid_thinkers, boid_attack_cool_down_system, boid_contact_damage_system, boid_contact_listener, boid_death_system, boid_steering, corpse_system, knockback_decay_system, load_boid_brains, quad_boid_flocking, reset_boids, spawn_boids, spawn_more_boids};
+use crate::{GameState, NewGame};
+use crate::events::damage::{BoidDiedEvent, PlayerDamagedEvent};
+use crate::systems::collisions::bullet_hit_boid_listener;
+use crate::systems::effects::status_effect_system;
+use crate::systems::explosions::explosion_system;
+use crate::systems::melee::melee_attack_system;
+use crate::systems::movement::linear_velocity_control_boid;
+
+/// Boid spawning, steering, flocking, death and the big-brain driven AI.
+pub struct BoidsPlugin {
+    pub brains: String,
+    pub brain: String,
+    pub initial_boids: usize,
+    pub cool_down: f32,
+    pub boids_to_generate: i32,
+    pub min_boids: usize,
+    pub max_boids: usize,
+    pub corpse_time: f32,
+    pub corpse_meat: i32,
+}
+
+impl Default for BoidsPlugin {
+    fn default() -> Self {
+        Self {
+            brains: "brains/boids.brains.ron".to_string(),
+            brain: "hunter".to_string(),
+            initial_boids: 100,
+            cool_down: 1.0,
+            boids_to_generate: 10,
+            min_boids: 500,
+            max_boids: 500,
+            corpse_time: 10.0,
+            corpse_meat: 20,
+        }
+    }
+}
+
+impl Plugin for BoidsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(BoidGenerationSettings::new(
+                self.cool_down,
+                self.boids_to_generate,
+                self.min_boids,
+                self.max_boids,
+                self.initial_boids,
+                self.brain.clone(),
+            ))
+            .insert_resource(CorpseSettings {
+                lifetime: self.corpse_time,
+                meat: self.corpse_meat,
+            })
+            .insert_resource(BoidBrains {
+                path: self.brains.clone(),
+                handle: Handle::default(),
+            })
+            .add_asset::<BoidBrainDefs>()
+            .init_asset_loader::<BoidBrainsLoader>()
+            .register_type::<BoidDirection>()
+            .register_type::<BoidStuff>()
+            .register_type::<Knockback>()
+            .register_type::<HuntTarget>()
+            .register_type::<Hunger>()
+            .add_event::<PlayerDamagedEvent>()
+            .add_event::<BoidDiedEvent>()
+            .add_plugins(BigBrainPlugin::new(PreUpdate))
+            .configure_sets(PreUpdate, (
+                BigBrainSet::Scorers.run_if(in_state(GameState::Playing)),
+                BigBrainSet::Thinkers.run_if(in_state(GameState::Playing)),
+                BigBrainSet::Actions.run_if(in_state(GameState::Playing)),
+            ))
+            .add_systems(Startup, load_boid_brains)
+            .add_systems(Update, attach_boid_thinkers)
+            .add_systems(Update, (
+                knockback_decay_system.before(linear_velocity_control_boid),
+                linear_velocity_control_boid,
+                boid_steering,
+                hunger_system,
+                boid_attack_cool_down_system,
+                boid_contact_listener.before(boid_death_system),
+                boid_contact_damage_system,
+                boid_death_system
+                    .after(bullet_hit_boid_listener)
+                    .after(explosion_system)
+                    .after(melee_attack_system)
+                    .after(status_effect_system),
+                corpse_system,
+            ).run_if(in_state(GameState::Playing)))
+            .add_systems(NewGame, (
+                reset_boids,
+                spawn_boids,
+            ).chain())
+            .add_systems(FixedUpdate, (
+                quad_boid_flocking,
+                spawn_more_boids,
+            ).run_if(in_state(GameState::Playing)))
+            .add_systems(
+                PreUpdate,
+                (
+                    (find_prey_action_system, hunt_prey_action_system, attack_and_eat_action_system, wander_action_system, wander_to_quadrant_action_system).in_set(BigBrainSet::Actions),
+                    hunger_scorer_system.in_set(BigBrainSet::Scorers),
+                ),
+            );
+    }
+}
@@ -0,0 +1,16 @@
+use bevy::prelude::{App, in_state, IntoSystemConfigs, Plugin, Update};
+use crate::components::effects::{StatusEffect, StatusEffects};
+use crate::GameState;
+use crate::systems::effects::status_effect_system;
+
+/// Counts status effects down and deals their damage over time.
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_type::<StatusEffect>()
+            .register_type::<StatusEffects>()
+            .add_systems(Update, status_effect_system.run_if(in_state(GameState::Playing)));
+    }
+}
@@ -0,0 +1,24 @@
+use bevy::prelude::{App, OnEnter, OnExit, OnTransition, Plugin, World};
+use crate::events::damage::PlayerDiedEvent;
+use crate::systems::game::{pause_game, resume_game};
+use crate::{GameState, NewGame};
+
+/// The `GameState` every other plugin hooks into, and the `NewGame` schedule.
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_state::<GameState>()
+            .init_schedule(NewGame)
+            .add_event::<PlayerDiedEvent>()
+            .add_systems(OnTransition { from: GameState::MainMenu, to: GameState::Playing }, run_new_game)
+            .add_systems(OnTransition { from: GameState::GameOver, to: GameState::Playing }, run_new_game)
+            .add_systems(OnEnter(GameState::Paused), pause_game)
+            .add_systems(OnExit(GameState::Paused), resume_game);
+    }
+}
+
+fn run_new_game(world: &mut World) {
+    world.run_schedule(NewGame);
+}
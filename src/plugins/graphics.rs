@@ -0,0 +1,42 @@
+use bevy::prelude::{App, default, GizmoConfig, Msaa, OnEnter, OnExit, Plugin, Startup, Update};
+use bevy_ecs_ldtk::prelude::LdtkPlugin;
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_prototype_lyon::plugin::ShapePlugin;
+use crate::systems::camera::camera_follow;
+use crate::systems::input::{add_mouse_aim_line, draw_mouse_aim};
+use crate::systems::sprites::attach_sprites;
+use crate::systems::startup::spawn_camera;
+use crate::systems::ui::{despawn_state_screen, spawn_game_over_screen, spawn_main_menu_screen, spawn_pause_screen};
+use crate::GameState;
+
+/// Everything that needs a window and a renderer, left out when running headless.
+pub struct GraphicsPlugin;
+
+impl Plugin for GraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Msaa::Sample4)
+            .insert_resource(GizmoConfig {
+                depth_bias: -1.0,
+                ..default()
+            })
+            .add_plugins(ShapePlugin)
+            .add_plugins(LdtkPlugin)
+            .add_plugins(WorldInspectorPlugin::new())
+            .add_systems(Startup, (
+                spawn_camera,
+                add_mouse_aim_line,
+            ))
+            .add_systems(Update, (
+                attach_sprites,
+                camera_follow,
+                draw_mouse_aim,
+            ))
+            .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu_screen)
+            .add_systems(OnExit(GameState::MainMenu), despawn_state_screen)
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_screen)
+            .add_systems(OnExit(GameState::Paused), despawn_state_screen)
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+            .add_systems(OnExit(GameState::GameOver), despawn_state_screen);
+    }
+}
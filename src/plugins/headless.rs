@@ -0,0 +1,67 @@
+use std::time::Duration;
+use bevy::app::AppExit;
+use bevy::hierarchy::HierarchyPlugin;
+use bevy::log::{info, LogPlugin};
+use bevy::prelude::{AddAsset, App, AssetPlugin, EventWriter, FixedUpdate, Image, MinimalPlugins, NextState, OnEnter, Plugin, ResMut, Resource, TransformPlugin};
+use bevy::time::TimeUpdateStrategy;
+use bevy_ecs_ldtk::{LdtkAsset, LdtkLevel, LdtkLoader};
+use crate::{FIXED_TIME_STEP, GameState};
+
+/// Runs the simulation without a window for a fixed number of `FixedUpdate` ticks, then exits.
+pub struct HeadlessPlugin {
+    pub ticks: u32,
+}
+
+impl Default for HeadlessPlugin {
+    fn default() -> Self {
+        Self { ticks: 1000 }
+    }
+}
+
+#[derive(Resource)]
+pub struct SimulationTicks {
+    pub elapsed: u32,
+    pub total: u32,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(MinimalPlugins)
+            .add_plugins((
+                LogPlugin::default(),
+                AssetPlugin::default(),
+                TransformPlugin,
+                HierarchyPlugin,
+            ))
+            // LdtkPlugin needs a renderer, the level's walls, water and pickups only need the project loaded
+            .add_asset::<Image>()
+            .add_asset::<LdtkAsset>()
+            .add_asset::<LdtkLevel>()
+            .init_asset_loader::<LdtkLoader>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(FIXED_TIME_STEP)))
+            .insert_resource(SimulationTicks {
+                elapsed: 0,
+                total: self.ticks,
+            })
+            .add_systems(FixedUpdate, count_simulation_ticks)
+            .add_systems(OnEnter(GameState::MainMenu), restart_game)
+            .add_systems(OnEnter(GameState::GameOver), restart_game);
+    }
+}
+
+pub fn count_simulation_ticks(
+    mut ticks: ResMut<SimulationTicks>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    ticks.elapsed += 1;
+    if ticks.elapsed >= ticks.total {
+        info!("Simulated {} ticks, exiting", ticks.elapsed);
+        app_exit.send(AppExit);
+    }
+}
+
+pub fn restart_game(mut next_state: ResMut<NextState<GameState>>) {
+    info!("Starting a new game");
+    next_state.set(GameState::Playing);
+}
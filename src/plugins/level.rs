@@ -0,0 +1,87 @@
+use bevy::prelude::{AddAsset, App, Handle, in_state, IntoSystemConfigs, Plugin, Startup, Update};
+use bevy_ecs_ldtk::prelude::{LdtkIntCellAppExt, LdtkSettings, LevelSelection, LevelSpawnBehavior, SetClearColor};
+use crate::boids::systems::boid_death_system;
+use crate::components::general::{WallBundle, WaterBundle};
+use crate::components::level::{LevelSettings, LevelStart, LoadedLevel, Terrain, TerrainFile, TerrainLoader};
+use crate::components::player::PlayerStartBundle;
+use crate::events::level::ChangeLevelEvent;
+use crate::GameState;
+use crate::systems::level::{build_level_terrain, change_level, load_selected_level, move_player_to_level_start, spawn_ldtk_world};
+use crate::systems::movement::{linear_velocity_control_player, push_out_of_walls};
+use crate::systems::terrain::{apply_terrain, keep_boids_out_of_water, load_terrain, slow_player_in_water, water_contact_listener};
+
+/// Loads the LDtk project at `path` and builds the walls and water of the level called `level`.
+/// Drawing the level is left to `GraphicsPlugin`, headless runs only need `HeadlessPlugin`'s LDtk assets.
+pub struct LevelPlugin {
+    pub path: String,
+    pub level: String,
+    pub wall: i32,
+    pub water: i32,
+    pub player_start: i32,
+    pub terrain_path: String,
+    pub terrain: Terrain,
+}
+
+impl Default for LevelPlugin {
+    fn default() -> Self {
+        Self {
+            path: "levels/world.ldtk".to_string(),
+            level: "Level_0".to_string(),
+            wall: 1,
+            water: 2,
+            player_start: 3,
+            terrain_path: "terrain/terrain.terrain.ron".to_string(),
+            terrain: Terrain::default(),
+        }
+    }
+}
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(LevelSettings {
+                path: self.path.clone(),
+                wall: self.wall,
+                water: self.water,
+                player_start: self.player_start,
+            })
+            .init_resource::<LoadedLevel>()
+            .init_resource::<LevelStart>()
+            .insert_resource(self.terrain.clone())
+            .insert_resource(TerrainFile {
+                path: self.terrain_path.clone(),
+                handle: Handle::default(),
+            })
+            .add_asset::<Terrain>()
+            .init_asset_loader::<TerrainLoader>()
+            .insert_resource(LevelSelection::Identifier(self.level.clone()))
+            .insert_resource(LdtkSettings {
+                level_spawn_behavior: LevelSpawnBehavior::UseWorldTranslation {
+                    load_level_neighbors: false,
+                },
+                set_clear_color: SetClearColor::FromLevelBackground,
+                ..Default::default()
+            })
+            .register_ldtk_int_cell::<WallBundle>(self.wall)
+            .register_ldtk_int_cell::<WaterBundle>(self.water)
+            .register_ldtk_int_cell::<PlayerStartBundle>(self.player_start)
+            .add_event::<ChangeLevelEvent>()
+            .add_systems(Startup, (
+                spawn_ldtk_world,
+                load_terrain,
+            ))
+            .add_systems(Update, apply_terrain)
+            .add_systems(Update, (
+                change_level,
+                load_selected_level.after(change_level),
+                build_level_terrain.after(load_selected_level),
+                move_player_to_level_start.after(build_level_terrain),
+            ))
+            .add_systems(Update, (
+                push_out_of_walls,
+                water_contact_listener.before(boid_death_system),
+                slow_player_in_water.after(linear_velocity_control_player),
+                keep_boids_out_of_water,
+            ).run_if(in_state(GameState::Playing)));
+    }
+}
@@ -0,0 +1,10 @@
+pub mod boids;
+pub mod weapons;
+pub mod spatial;
+pub mod player;
+pub mod graphics;
+pub mod headless;
+pub mod pickups;
+pub mod game;
+pub mod effects;
+pub mod level;
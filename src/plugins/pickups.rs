@@ -0,0 +1,25 @@
+use bevy::prelude::{App, in_state, IntoSystemConfigs, Plugin, resource_exists_and_changed, Update};
+use crate::components::level::LoadedLevel;
+use crate::components::pickup::{LootTable, Pickup};
+use crate::systems::pickups::{despawn_dropped_pickups, drop_loot_from_boids, pickup_system, spawn_level_pickups};
+use crate::{GameState, NewGame};
+
+/// Weapons, ammo and health for the player to pick up.
+#[derive(Default)]
+pub struct PickupsPlugin {
+    pub loot: LootTable,
+}
+
+impl Plugin for PickupsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(self.loot.clone())
+            .register_type::<Pickup>()
+            .add_systems(Update, (
+                pickup_system,
+                drop_loot_from_boids,
+            ).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, spawn_level_pickups.run_if(resource_exists_and_changed::<LoadedLevel>()))
+            .add_systems(NewGame, (despawn_dropped_pickups, spawn_level_pickups));
+    }
+}
@@ -0,0 +1,60 @@
+use bevy::math::Vec2;
+use bevy::prelude::{App, in_state, IntoSystemConfigs, Plugin, Update};
+use crate::components::control::{PlayerControl, PlayerController, PlayerScript};
+use crate::components::general::Health;
+use crate::components::player::PlayerSettings;
+use crate::systems::input::{game_state_input, keyboard_input, mouse_key_input, mouse_look, mouse_position, mouse_wheel_input};
+use crate::systems::movement::linear_velocity_control_player;
+use crate::systems::player::{player_death_system, respawn_player, scripted_player_control};
+use crate::{GameState, NewGame};
+
+/// The player and whatever controls it.
+pub struct PlayerPlugin {
+    pub start_position: Vec2,
+    pub controller: PlayerController,
+}
+
+impl Default for PlayerPlugin {
+    fn default() -> Self {
+        Self {
+            start_position: Vec2::ZERO,
+            controller: PlayerController::Keyboard,
+        }
+    }
+}
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(PlayerSettings {
+                start_position: self.start_position,
+            })
+            .register_type::<PlayerControl>()
+            .register_type::<Health>()
+            .add_systems(NewGame, respawn_player)
+            .add_systems(Update, (
+                mouse_look,
+                linear_velocity_control_player,
+                player_death_system,
+            ).run_if(in_state(GameState::Playing)));
+
+        match &self.controller {
+            PlayerController::Keyboard => {
+                app
+                    .add_systems(Update, (
+                        keyboard_input,
+                        mouse_key_input,
+                        mouse_wheel_input,
+                        mouse_position,
+                    ).run_if(in_state(GameState::Playing)))
+                    .add_systems(Update, game_state_input);
+            }
+            PlayerController::Idle => {}
+            PlayerController::Scripted(steps) => {
+                app
+                    .insert_resource(PlayerScript::new(steps.clone()))
+                    .add_systems(Update, scripted_player_control.run_if(in_state(GameState::Playing)));
+            }
+        }
+    }
+}
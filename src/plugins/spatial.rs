@@ -0,0 +1,50 @@
+use bevy::prelude::{App, FixedUpdate, in_state, IntoSystemConfigs, Plugin, PreUpdate};
+use crate::components::quad::{QuadCoord, QuadStore};
+use crate::systems::quads::{check_quad_store, clear_quad_store, naive_quad_system, remove_from_quad_store};
+use crate::{GameState, NewGame};
+
+/// Buckets every entity with a `QuadCoord` into the `QuadStore` grid.
+pub struct SpatialPlugin {
+    pub quad_size: f32,
+    pub min_quad_size: f32,
+    pub max_quad_size: f32,
+    pub min_entities: usize,
+    pub max_entities: usize,
+    pub check_consistency: bool,
+}
+
+impl Default for SpatialPlugin {
+    fn default() -> Self {
+        Self {
+            quad_size: 128.0,
+            min_quad_size: 16.0,
+            max_quad_size: 1024.0,
+            min_entities: 50,
+            max_entities: 200,
+            check_consistency: false,
+        }
+    }
+}
+
+impl Plugin for SpatialPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(QuadStore::new(
+                self.quad_size,
+                self.min_quad_size,
+                self.max_quad_size,
+                self.min_entities,
+                self.max_entities,
+            ))
+            .register_type::<QuadCoord>()
+            .add_systems(PreUpdate, remove_from_quad_store)
+            .add_systems(FixedUpdate, naive_quad_system.run_if(in_state(GameState::Playing)))
+            .add_systems(NewGame, clear_quad_store);
+
+        if self.check_consistency {
+            app.add_systems(FixedUpdate, check_quad_store
+                .after(naive_quad_system)
+                .run_if(in_state(GameState::Playing)));
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::{AddAsset, App, Handle, in_state, IntoSystemConfigs, Plugin, Startup, Update};
+use crate::components::player::WeaponInventory;
+use crate::components::weapon::{CurrentWeapon, FriendlyFire, WeaponModifier, WeaponDefs, WeaponDefsAsset, WeaponDefsFile, WeaponDefsLoader, WeaponSwitchSettings};
+use crate::events::ammo::{OutOfAmmoEvent, ReloadFinishedEvent, ReloadStartedEvent};
+use crate::events::collisions::{BoidHitPlayerEvent, BulletHitBoidEvent, BulletHitPlayerEvent, BulletHitWallEvent};
+use crate::events::damage::DamageEvent;
+use crate::events::explosions::ExplosionEvent;
+use crate::events::weapons::WeaponSwitchedEvent;
+use crate::systems::collisions::{bullet_hit_boid_listener, bullet_hit_player_listener, bullet_hit_wall_listener, collision_event_listener, grenade_hit_wall_listener};
+use crate::systems::explosions::{explosion_system, explosive_hit_listener, grenade_fuse_system};
+use crate::systems::melee::melee_attack_system;
+use crate::systems::player::switch_weapon_system;
+use crate::systems::projectiles::{despawn_projectiles, projectile_lifetime_system};
+use crate::{GameState, NewGame};
+use crate::systems::shooting::shooting_system;
+use crate::systems::weapons::{apply_weapon_defs, load_weapon_defs, reload_system};
+
+/// Weapon definitions, shooting, reloading, switching weapons and what projectiles hit.
+pub struct WeaponsPlugin {
+    pub path: String,
+    pub weapon_defs: WeaponDefs,
+    pub friendly_fire: bool,
+    pub switch_delay: f32,
+}
+
+impl Default for WeaponsPlugin {
+    fn default() -> Self {
+        Self {
+            path: "weapons/weapons.weapons.ron".to_string(),
+            weapon_defs: WeaponDefs::default(),
+            friendly_fire: false,
+            switch_delay: 0.3,
+        }
+    }
+}
+
+impl Plugin for WeaponsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(self.weapon_defs.clone())
+            .insert_resource(FriendlyFire {
+                enabled: self.friendly_fire,
+            })
+            .insert_resource(WeaponSwitchSettings {
+                delay: self.switch_delay,
+            })
+            .insert_resource(WeaponDefsFile {
+                path: self.path.clone(),
+                handle: Handle::default(),
+            })
+            .add_asset::<WeaponDefsAsset>()
+            .init_asset_loader::<WeaponDefsLoader>()
+            .register_type::<CurrentWeapon>()
+            .register_type::<WeaponInventory>()
+            .register_type::<WeaponModifier>()
+            .add_event::<BoidHitPlayerEvent>()
+            .add_event::<BulletHitBoidEvent>()
+            .add_event::<BulletHitPlayerEvent>()
+            .add_event::<BulletHitWallEvent>()
+            .add_event::<DamageEvent>()
+            .add_event::<ExplosionEvent>()
+            .add_event::<OutOfAmmoEvent>()
+            .add_event::<ReloadFinishedEvent>()
+            .add_event::<ReloadStartedEvent>()
+            .add_event::<WeaponSwitchedEvent>()
+            .add_systems(Startup, load_weapon_defs)
+            .add_systems(NewGame, despawn_projectiles)
+            .add_systems(Update, apply_weapon_defs)
+            .add_systems(Update, (
+                switch_weapon_system,
+                reload_system,
+                shooting_system,
+                melee_attack_system,
+                collision_event_listener,
+                bullet_hit_boid_listener,
+                bullet_hit_wall_listener,
+                grenade_hit_wall_listener,
+                bullet_hit_player_listener,
+                projectile_lifetime_system,
+                explosive_hit_listener,
+                grenade_fuse_system,
+                explosion_system,
+            ).run_if(in_state(GameState::Playing)));
+    }
+}
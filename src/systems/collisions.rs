@@ -1,30 +1,172 @@
 use bevy::prelude::*;
 use bevy_xpbd_2d::components::LinearVelocity;
-use bevy_xpbd_2d::prelude::{CollisionStarted, ExternalForce};
-use crate::boids::components::Boid;
+use bevy::utils::HashSet;
+use bevy_xpbd_2d::prelude::{Collider, CollisionStarted, Position};
+use crate::boids::components::{Boid, Knockback};
+use crate::components::effects::StatusEffects;
 use crate::components::general::Health;
 use crate::components::general::Wall;
 use crate::components::player::Player;
-use crate::components::weapon::Projectile;
+use crate::components::weapon::{Explosive, FriendlyFire, Fuse, Piercing, Projectile, ProjectileDamage, Ricochet, Shooter};
 use crate::events::collisions::{BoidHitPlayerEvent, BulletHitBoidEvent, BulletHitPlayerEvent, BulletHitWallEvent};
+use crate::events::damage::DamageEvent;
+use crate::events::explosions::ExplosionEvent;
 
+/*
+A piercing bullet flies on through the boid it hit, with less damage, until it has
+been through as many boids as it may. A bullet can touch several boids in the same
+frame, once it is spent it is not allowed to hit any of the others. Boids killed
+earlier in the frame are still around until `boid_death_system` removes them, bullets
+fly on through those.
+ */
 pub fn bullet_hit_boid_listener(
     mut bullet_hit_boid_event_reader: EventReader<BulletHitBoidEvent>,
     mut commands: Commands,
-    mut boid_query: Query<(&mut Health, &mut ExternalForce), With<Boid>>,
-    bullet_query: Query<&LinearVelocity, With<Projectile>>,
+    mut boid_query: Query<(&mut Health, &mut Knockback, &mut StatusEffects), With<Boid>>,
+    mut bullet_query: Query<(&LinearVelocity, &mut ProjectileDamage, &Shooter, Option<&mut Piercing>), (With<Projectile>, Without<Explosive>)>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
 ) {
+    let mut spent = HashSet::new();
     for BulletHitBoidEvent { bullet, boid } in bullet_hit_boid_event_reader.iter() {
-        if let Ok(linear_velocity) = bullet_query.get(*bullet) {
-            let _bullet_direction = linear_velocity.0.clone().normalize_or_zero();
-
-            if let Ok((mut health, mut _external_force)) = boid_query.get_mut(*boid) {
-                health.health -= 50;
+        if spent.contains(bullet) {
+            continue;
+        }
+        if let Ok((linear_velocity, mut damage, shooter, piercing)) = bullet_query.get_mut(*bullet) {
+            if let Ok((mut health, mut knockback, mut status_effects)) = boid_query.get_mut(*boid) {
                 if health.health <= 0 {
-                    commands.entity(*boid).despawn();
+                    continue;
+                }
+                health.health -= damage.amount;
+                for effect in damage.effects.iter() {
+                    status_effects.apply(effect, shooter.0);
+                }
+                if !status_effects.is_knockback_immune() {
+                    knockback.push(linear_velocity.0, damage.knockback);
+                }
+                damage_event_writer.send(DamageEvent {
+                    shooter: shooter.0,
+                    target: *boid,
+                    amount: damage.amount,
+                    weapon: damage.weapon.clone(),
+                });
+            }
+            match piercing {
+                Some(mut piercing) if piercing.boids_left > 0 => {
+                    piercing.boids_left -= 1;
+                    damage.amount = (damage.amount as f32 * piercing.damage_kept).round().max(1.0) as i32;
+                }
+                _ => {
+                    spent.insert(*bullet);
+                    commands.entity(*bullet).despawn();
                 }
             }
         }
+    }
+}
+
+/// Bullets bounce off walls while they have bounces left and stop at them after that,
+/// rockets exploding on them is handled by `explosive_hit_listener`, grenades bouncing
+/// off them by `grenade_hit_wall_listener`.
+pub fn bullet_hit_wall_listener(
+    mut bullet_hit_wall_event_reader: EventReader<BulletHitWallEvent>,
+    mut bullet_query: Query<(&Position, &mut LinearVelocity, Option<&mut Ricochet>), (With<Projectile>, Without<Explosive>)>,
+    wall_query: Query<(&Position, Option<&Collider>), With<Wall>>,
+    mut commands: Commands,
+) {
+    // Bullets hitting a corner touch two walls at once, they should only bounce once
+    let mut handled = HashSet::new();
+    for BulletHitWallEvent { bullet, wall } in bullet_hit_wall_event_reader.iter() {
+        if !handled.insert(*bullet) {
+            continue;
+        }
+        let Ok((bullet_position, mut linear_velocity, ricochet)) = bullet_query.get_mut(*bullet) else { continue; };
+        match (ricochet, wall_query.get(*wall)) {
+            (Some(mut ricochet), Ok((wall_position, collider))) if ricochet.bounces_left > 0 => {
+                ricochet.bounces_left -= 1;
+                linear_velocity.0 = bounce_off_wall(bullet_position.0, linear_velocity.0, wall_position.0, collider);
+            }
+            _ => {
+                commands.entity(*bullet).despawn();
+            }
+        }
+    }
+}
+
+/// Grenades bounce off every wall they hit, their fuse decides when they go off.
+pub fn grenade_hit_wall_listener(
+    mut bullet_hit_wall_event_reader: EventReader<BulletHitWallEvent>,
+    mut grenade_query: Query<(&Position, &mut LinearVelocity), (With<Projectile>, With<Fuse>)>,
+    wall_query: Query<(&Position, Option<&Collider>), With<Wall>>,
+) {
+    let mut handled = HashSet::new();
+    for BulletHitWallEvent { bullet, wall } in bullet_hit_wall_event_reader.iter() {
+        if !handled.insert(*bullet) {
+            continue;
+        }
+        let Ok((grenade_position, mut linear_velocity)) = grenade_query.get_mut(*bullet) else { continue; };
+        if let Ok((wall_position, collider)) = wall_query.get(*wall) {
+            linear_velocity.0 = bounce_off_wall(grenade_position.0, linear_velocity.0, wall_position.0, collider);
+        }
+    }
+}
+
+/// Mirrors the velocity in the side of the wall we hit, unless we are already moving away from it.
+fn bounce_off_wall(position: Vec2, velocity: Vec2, wall_position: Vec2, wall_collider: Option<&Collider>) -> Vec2 {
+    let half_extents = wall_collider
+        .and_then(|collider| collider.get_shape().as_cuboid())
+        .map(|cuboid| Vec2::new(cuboid.half_extents.x, cuboid.half_extents.y))
+        .unwrap_or(Vec2::splat(0.5));
+    let normal = wall_normal(position, wall_position, half_extents);
+    if velocity.dot(normal) < 0.0 {
+        velocity - 2.0 * velocity.dot(normal) * normal
+    } else {
+        velocity
+    }
+}
+
+/// Walls are axis aligned boxes, we bounce off whichever side of the box the bullet is at.
+fn wall_normal(bullet_position: Vec2, wall_position: Vec2, half_extents: Vec2) -> Vec2 {
+    let offset = (bullet_position - wall_position) / half_extents.max(Vec2::splat(f32::EPSILON));
+    if offset.x.abs() > offset.y.abs() {
+        Vec2::new(offset.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, offset.y.signum())
+    }
+}
+
+/*
+Projectiles are spawned on top of whoever fired them, so the shooter is always
+touched by their own shots, those are ignored. Hits the `FriendlyFire` rules let
+through hurt the player, or blow up if the projectile is a rocket.
+ */
+pub fn bullet_hit_player_listener(
+    mut bullet_hit_player_event_reader: EventReader<BulletHitPlayerEvent>,
+    friendly_fire: Res<FriendlyFire>,
+    mut player_query: Query<(&mut Health, &mut StatusEffects), With<Player>>,
+    bullet_query: Query<(&Position, &ProjectileDamage, &Shooter, Option<&Explosive>), With<Projectile>>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
+    mut explosion_event_writer: EventWriter<ExplosionEvent>,
+    mut commands: Commands,
+) {
+    for BulletHitPlayerEvent { bullet, player } in bullet_hit_player_event_reader.iter() {
+        let Ok((position, damage, shooter, explosive)) = bullet_query.get(*bullet) else { continue; };
+        if !friendly_fire.allows(shooter.0, *player, player_query.contains(shooter.0)) {
+            continue;
+        }
+        if let Some(explosive) = explosive {
+            explosion_event_writer.send(ExplosionEvent::from_projectile(position.0, explosive, damage, shooter.0));
+        } else if let Ok((mut health, mut status_effects)) = player_query.get_mut(*player) {
+            health.health -= damage.amount;
+            for effect in damage.effects.iter() {
+                status_effects.apply(effect, shooter.0);
+            }
+            damage_event_writer.send(DamageEvent {
+                shooter: shooter.0,
+                target: *player,
+                amount: damage.amount,
+                weapon: damage.weapon.clone(),
+            });
+        }
         commands.entity(*bullet).despawn();
     }
 }
@@ -46,9 +188,9 @@ pub fn collision_event_listener(
          */
         if bullet_query.contains(*entity1) || bullet_query.contains(*entity2) {
             let (bullet_entity, other_entity) = if bullet_query.contains(*entity1) {
-                (&*entity1, &*entity2)
+                (entity1, entity2)
             } else {
-                (&*entity2, &*entity1)
+                (entity2, entity1)
             };
 
             if boid_query.contains(*other_entity) {
@@ -71,9 +213,9 @@ pub fn collision_event_listener(
             (boid_query.contains(*entity1) || boid_query.contains(*entity2)) {
             /* This is boid on player hit */
             let (boid_entity, player_entity) = if boid_query.contains(*entity1) {
-                (&*entity1, &*entity2)
+                (entity1, entity2)
             } else {
-                (&*entity2, &*entity1)
+                (entity2, entity1)
             };
             ev_boid_player.send(BoidHitPlayerEvent {
                 boid: *boid_entity,
@@ -81,4 +223,101 @@ pub fn collision_event_listener(
             })
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use bevy_rand::prelude::EntropyPlugin;
+    use bevy_xpbd_2d::prelude::{Gravity, PhysicsPlugins, PhysicsTimestep};
+    use rand_chacha::ChaCha8Rng;
+    use std::time::Duration;
+    use crate::boids::components::{BoidBundle, BoidStuff};
+    use crate::boids::systems::{boid_contact_damage_system, boid_contact_listener};
+    use crate::components::player::PlayerBundle;
+    use crate::components::weapon::{ProjectileBundle, ProjectileLifetime};
+    use crate::Layer;
+    use bevy_xpbd_2d::prelude::CollisionLayers;
+    use crate::events::damage::PlayerDamagedEvent;
+
+    /// Physics steps once per update, the collision systems run on the events of the step before.
+    fn collision_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, PhysicsPlugins::default(), EntropyPlugin::<ChaCha8Rng>::with_seed([7; 32])))
+            .insert_resource(Gravity(Vec2::ZERO))
+            .insert_resource(PhysicsTimestep::FixedOnce(1.0 / 60.0))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(1.0 / 60.0)))
+            .add_event::<BoidHitPlayerEvent>()
+            .add_event::<BulletHitBoidEvent>()
+            .add_event::<BulletHitPlayerEvent>()
+            .add_event::<BulletHitWallEvent>()
+            .add_event::<PlayerDamagedEvent>()
+            .add_event::<DamageEvent>()
+            .add_event::<ExplosionEvent>()
+            .add_systems(Update, collision_event_listener);
+        app
+    }
+
+    fn run(app: &mut App, updates: usize) {
+        for _ in 0..updates {
+            app.update();
+        }
+    }
+
+    fn health(app: &App, entity: Entity) -> i32 {
+        app.world.get::<Health>(entity).unwrap().health
+    }
+
+    #[test]
+    fn boid_on_top_of_the_player_bites_it() {
+        let mut app = collision_app();
+        app.add_systems(Update, (boid_contact_listener, boid_contact_damage_system).after(collision_event_listener));
+        let player = app.world.spawn(PlayerBundle::default()).id();
+        let mut boid = BoidBundle::new("Boid".to_string(), Vec2::ZERO, Vec2::X, 5..6, 1.0, 100, BoidStuff::default());
+        boid.boid_attack.cool_down = -1.0;
+        app.world.spawn(boid);
+
+        run(&mut app, 5);
+
+        assert_eq!(health(&app, player), 95);
+    }
+
+    /// Player two stands where player one fires a pistol round.
+    fn shot_at_other_player(friendly_fire: bool) -> i32 {
+        let mut app = collision_app();
+        app.insert_resource(FriendlyFire { enabled: friendly_fire })
+            .add_systems(Update, bullet_hit_player_listener.after(collision_event_listener));
+        let shooter = app.world.spawn(PlayerBundle::default()).id();
+        let target = app.world.spawn(PlayerBundle::default()).id();
+        app.world.spawn(ProjectileBundle::new(
+            "Bullet".to_string(),
+            Position(Vec2::ZERO),
+            Vec2::ZERO,
+            Collider::ball(0.5),
+            CollisionLayers::new([Layer::Bullet], [Layer::Player, Layer::Boid, Layer::Walls, Layer::Water]),
+            shooter,
+            ProjectileDamage {
+                amount: 10,
+                weapon: "Pistol".to_string(),
+                effects: vec![],
+                knockback: 0.0,
+            },
+            ProjectileLifetime {
+                origin: Vec2::ZERO,
+                max_range: 100.0,
+                time_left: 1.0,
+            },
+        ));
+
+        run(&mut app, 5);
+
+        assert_eq!(health(&app, shooter), 100);
+        health(&app, target)
+    }
+
+    #[test]
+    fn friendly_fire_hurts_other_players_only_when_enabled() {
+        assert_eq!(shot_at_other_player(true), 90);
+        assert_eq!(shot_at_other_player(false), 100);
+    }
+}
@@ -0,0 +1,30 @@
+use bevy::prelude::{Entity, EventWriter, Query, Res, Time};
+use crate::components::effects::StatusEffects;
+use crate::components::general::Health;
+use crate::events::damage::DamageEvent;
+
+/// Counts down status effects and deals their damage over time, to whoever applied them.
+/// Whoever burns or bleeds to death is left to `boid_death_system` and `player_death_system`.
+pub fn status_effect_system(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut StatusEffects, &mut Health)>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
+) {
+    for (entity, mut status_effects, mut health) in query.iter_mut() {
+        if status_effects.active.is_empty() || health.health <= 0 {
+            continue;
+        }
+        for damage in status_effects.tick(time.delta_seconds()) {
+            health.health -= damage.amount;
+            damage_event_writer.send(DamageEvent {
+                shooter: damage.source,
+                target: entity,
+                amount: damage.amount,
+                weapon: damage.effect.to_string(),
+            });
+            if health.health <= 0 {
+                break;
+            }
+        }
+    }
+}
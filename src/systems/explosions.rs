@@ -0,0 +1,87 @@
+use bevy::prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, Time, With, Without};
+use bevy::utils::HashSet;
+use bevy_xpbd_2d::components::Position;
+use crate::boids::components::{Boid, Knockback};
+use crate::components::general::Health;
+use crate::components::quad::QuadStore;
+use crate::components::effects::StatusEffects;
+use crate::components::weapon::{Explosive, Fuse, ProjectileDamage, Shooter};
+use crate::events::collisions::{BulletHitBoidEvent, BulletHitWallEvent};
+use crate::events::damage::DamageEvent;
+use crate::events::explosions::ExplosionEvent;
+
+/// Rockets go off as soon as they touch a boid or a wall.
+pub fn explosive_hit_listener(
+    mut bullet_hit_boid_event_reader: EventReader<BulletHitBoidEvent>,
+    mut bullet_hit_wall_event_reader: EventReader<BulletHitWallEvent>,
+    explosive_query: Query<(&Position, &Explosive, &ProjectileDamage, &Shooter), Without<Fuse>>,
+    mut explosion_event_writer: EventWriter<ExplosionEvent>,
+    mut commands: Commands,
+) {
+    // A rocket can touch several things in the same frame, it should only explode once
+    let hits = bullet_hit_boid_event_reader.iter().map(|hit| hit.bullet)
+        .chain(bullet_hit_wall_event_reader.iter().map(|hit| hit.bullet))
+        .collect::<HashSet<Entity>>();
+
+    for bullet in hits {
+        if let Ok((position, explosive, damage, shooter)) = explosive_query.get(bullet) {
+            explosion_event_writer.send(ExplosionEvent::from_projectile(position.0, explosive, damage, shooter.0));
+            commands.entity(bullet).despawn();
+        }
+    }
+}
+
+pub fn grenade_fuse_system(
+    time: Res<Time>,
+    mut grenade_query: Query<(Entity, &Position, &mut Fuse, &Explosive, &ProjectileDamage, &Shooter)>,
+    mut explosion_event_writer: EventWriter<ExplosionEvent>,
+    mut commands: Commands,
+) {
+    for (grenade, position, mut fuse, explosive, damage, shooter) in grenade_query.iter_mut() {
+        fuse.time_left -= time.delta_seconds();
+        if fuse.time_left <= 0.0 {
+            explosion_event_writer.send(ExplosionEvent::from_projectile(position.0, explosive, damage, shooter.0));
+            commands.entity(grenade).despawn();
+        }
+    }
+}
+
+/*
+Damage and knockback fall off linearly from the full amount at the centre
+to nothing at the edge of the blast.
+ */
+pub fn explosion_system(
+    mut explosion_event_reader: EventReader<ExplosionEvent>,
+    quad_store: Res<QuadStore>,
+    mut boid_query: Query<(&Position, &mut Health, &mut Knockback, &mut StatusEffects), With<Boid>>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
+) {
+    for explosion in explosion_event_reader.iter() {
+        for boid in quad_store.entities_within(explosion.position, explosion.radius) {
+            if let Ok((position, mut health, mut knockback, mut status_effects)) = boid_query.get_mut(boid) {
+                let delta = position.0 - explosion.position;
+                let distance = delta.length();
+                if distance > explosion.radius || health.health <= 0 {
+                    continue;
+                }
+                let falloff = 1.0 - distance / explosion.radius;
+                let amount = (explosion.damage as f32 * falloff).round() as i32;
+                if amount > 0 {
+                    health.health -= amount;
+                    damage_event_writer.send(DamageEvent {
+                        shooter: explosion.shooter,
+                        target: boid,
+                        amount,
+                        weapon: explosion.weapon.clone(),
+                    });
+                }
+                for effect in explosion.effects.iter() {
+                    status_effects.apply(effect, explosion.shooter);
+                }
+                if !status_effects.is_knockback_immune() {
+                    knockback.push(delta, explosion.force * falloff);
+                }
+            }
+        }
+    }
+}
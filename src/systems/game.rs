@@ -0,0 +1,12 @@
+use bevy::prelude::{ResMut, Time};
+use bevy_xpbd_2d::prelude::PhysicsLoop;
+
+pub fn pause_game(mut time: ResMut<Time>, mut physics_loop: ResMut<PhysicsLoop>) {
+    time.pause();
+    physics_loop.pause();
+}
+
+pub fn resume_game(mut time: ResMut<Time>, mut physics_loop: ResMut<PhysicsLoop>) {
+    time.unpause();
+    physics_loop.resume();
+}
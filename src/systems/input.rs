@@ -1,4 +1,4 @@
-use bevy::prelude::{Camera, Color, Commands, default, Entity, EventReader, GlobalTransform, MouseButton, Query, Transform, Window, With};
+use bevy::prelude::{Camera, Color, Commands, default, Entity, EventReader, GlobalTransform, MouseButton, NextState, Query, Res, ResMut, State, Transform, Window, With, Without};
 use bevy::window::PrimaryWindow;
 use bevy_prototype_lyon::shapes;
 use bevy::math::Vec2;
@@ -10,15 +10,16 @@ use bevy_prototype_lyon::path::ShapePath;
 use bevy::input::keyboard::KeyboardInput;
 use std::ops::AddAssign;
 use bevy::input::ButtonState;
-use bevy::input::mouse::MouseButtonInput;
-use crate::components::control::{CycleDirection, CycleWeapon, PlayerControl, TriggerPulled};
-use crate::components::player::Player;
+use bevy::input::mouse::{MouseButtonInput, MouseWheel};
+use crate::components::control::{CycleDirection, CycleWeapon, PlayerControl, ReloadWeapon, SelectWeapon, TriggerPulled};
+use crate::components::player::{Dead, Player};
 use bevy::prelude::KeyCode;
 use crate::components::general::{AimLine, GameCam};
+use crate::GameState;
 
 pub fn keyboard_input(
     mut key_evr: EventReader<KeyboardInput>,
-    mut query: Query<(Entity, &mut PlayerControl), With<Player>>,
+    mut query: Query<(Entity, &mut PlayerControl), (With<Player>, Without<Dead>)>,
     mut commands: Commands,
 ) {
     if let Ok((entity, mut player_control)) = query.get_single_mut() {
@@ -40,6 +41,9 @@ pub fn keyboard_input(
                     Some(KeyCode::Space) => {
                         commands.entity(entity).insert(TriggerPulled {});
                     }
+                    Some(KeyCode::R) => {
+                        commands.entity(entity).insert(ReloadWeapon {});
+                    }
                     _ => {}
                 },
                 ButtonState::Released => match ev.key_code {
@@ -68,6 +72,11 @@ pub fn keyboard_input(
                     Some(KeyCode::Space) => {
                         commands.entity(entity).remove::<TriggerPulled>();
                     }
+                    Some(key_code) => {
+                        if let Some(slot) = weapon_slot(key_code) {
+                            commands.entity(entity).insert(SelectWeapon { slot });
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -76,28 +85,86 @@ pub fn keyboard_input(
     }
 }
 
+/// Keys 1 to 9 select the first nine weapon slots.
+fn weapon_slot(key_code: KeyCode) -> Option<usize> {
+    [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+        KeyCode::Key9,
+    ]
+        .iter()
+        .position(|slot_key| *slot_key == key_code)
+}
+
+pub fn mouse_wheel_input(
+    mut mouse_wheel_reader: EventReader<MouseWheel>,
+    query: Query<Entity, (With<Player>, Without<Dead>)>,
+    mut commands: Commands,
+) {
+    if let Ok(entity) = query.get_single() {
+        for ev in mouse_wheel_reader.iter() {
+            if ev.y < 0.0 {
+                commands.entity(entity).insert(CycleWeapon {
+                    direction: CycleDirection::Forward,
+                });
+            } else if ev.y > 0.0 {
+                commands.entity(entity).insert(CycleWeapon {
+                    direction: CycleDirection::Backward,
+                });
+            }
+        }
+    }
+}
+
+/// Enter starts a new game from the main menu or once the game is over, Escape pauses
+/// and resumes the game and quits to the main menu from the game over screen, and Q
+/// quits to the main menu while paused.
+pub fn game_state_input(
+    mut key_evr: EventReader<KeyboardInput>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for ev in key_evr.iter() {
+        if ev.state != ButtonState::Released {
+            continue;
+        }
+        let next = match (state.get(), ev.key_code) {
+            (GameState::MainMenu, Some(KeyCode::Return)) => Some(GameState::Playing),
+            (GameState::Playing, Some(KeyCode::Escape)) => Some(GameState::Paused),
+            (GameState::Paused, Some(KeyCode::Escape)) => Some(GameState::Playing),
+            (GameState::Paused, Some(KeyCode::Q)) => Some(GameState::MainMenu),
+            (GameState::GameOver, Some(KeyCode::Return)) => Some(GameState::Playing),
+            (GameState::GameOver, Some(KeyCode::Escape)) => Some(GameState::MainMenu),
+            _ => None,
+        };
+        if let Some(next) = next {
+            next_state.set(next);
+        }
+    }
+}
+
 pub fn mouse_key_input(
     mut mouse_ev_reader: EventReader<MouseButtonInput>,
-    mut query: Query<Entity, With<Player>>,
+    mut query: Query<Entity, (With<Player>, Without<Dead>)>,
     mut commands: Commands) {
     if let Ok(entity) = query.get_single_mut() {
         for ev in mouse_ev_reader.iter() {
             match ev.state {
                 ButtonState::Pressed => {
-                    match ev.button {
-                        MouseButton::Left => {
-                            commands.entity(entity).insert(TriggerPulled {});
-                        }
-                        _ => {}
+                    if ev.button == MouseButton::Left {
+                        commands.entity(entity).insert(TriggerPulled {});
                     }
                 }
 
                 ButtonState::Released => {
-                    match ev.button {
-                        MouseButton::Left => {
-                            commands.entity(entity).remove::<TriggerPulled>();
-                        }
-                        _ => {}
+                    if ev.button == MouseButton::Left {
+                        commands.entity(entity).remove::<TriggerPulled>();
                     }
                 }
             }
@@ -112,10 +179,10 @@ pub fn mouse_position(
     // query to get camera transform
     camera_q: Query<(&Camera, &GlobalTransform), With<GameCam>>,
 ) {
-    let (camera, camera_transform) = camera_q.single();
-    let mut direction_control = q_direction.single_mut();
-    if let Some(position) = q_windows
-        .single()
+    let Ok((camera, camera_transform)) = camera_q.get_single() else { return; };
+    let Ok(window) = q_windows.get_single() else { return; };
+    let Ok(mut direction_control) = q_direction.get_single_mut() else { return; };
+    if let Some(position) = window
         .cursor_position()
         .and_then(|cursor|
             camera.viewport_to_world(camera_transform, cursor))
@@ -142,7 +209,7 @@ pub fn draw_mouse_aim(
     q_mouse_aim: Query<(&Transform, &PlayerControl), With<Player>>,
     mut query: Query<&mut Path, With<AimLine>>,
 ) {
-    let (transform, direction_control) = q_mouse_aim.single();
+    let Ok((transform, direction_control)) = q_mouse_aim.get_single() else { return; };
     let mut path = query.single_mut();
     let line = shapes::Line(Vec2::new(transform.translation.x, transform.translation.y), direction_control.mouse_position);
     *path = ShapePath::build_as(&line)
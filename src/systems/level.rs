@@ -0,0 +1,313 @@
+use bevy::asset::{AssetEvent, Assets, AssetServer, Handle};
+use bevy::math::{IVec2, Vec2, Vec3};
+use bevy::prelude::{Commands, default, DetectChanges, Entity, EventReader, Or, Query, Res, ResMut, Transform, With};
+use bevy::utils::HashSet;
+use bevy_ecs_ldtk::ldtk::{LayerInstance, Level};
+use bevy_ecs_ldtk::prelude::{LdtkAsset, LdtkWorldBundle, LevelSelection};
+use bevy_ecs_ldtk::utils::{ldtk_pixel_coords_to_translation, ldtk_pixel_coords_to_translation_pivoted};
+use bevy_xpbd_2d::prelude::Position;
+use crate::components::general::{InWater, WallCollider, WallColliderBundle, WaterArea, WaterAreaBundle};
+use crate::components::level::{LevelSettings, LevelStart, LoadedLevel};
+use crate::components::pickup::Pickup;
+use crate::components::player::Player;
+use crate::events::level::ChangeLevelEvent;
+use crate::METERS_PER_PIXEL;
+
+/// LDtk works in pixels and we work in meters, so the whole world is scaled down.
+pub fn spawn_ldtk_world(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level_settings: Res<LevelSettings>,
+) {
+    commands.spawn(LdtkWorldBundle {
+        ldtk_handle: asset_server.load(level_settings.path.as_str()),
+        transform: Transform::from_scale(Vec3::new(
+            METERS_PER_PIXEL,
+            METERS_PER_PIXEL,
+            1.0,
+        )),
+        ..default()
+    });
+}
+
+pub fn change_level(
+    mut change_level_reader: EventReader<ChangeLevelEvent>,
+    mut level_selection: ResMut<LevelSelection>,
+) {
+    if let Some(ChangeLevelEvent { identifier }) = change_level_reader.iter().last() {
+        *level_selection = LevelSelection::Identifier(identifier.clone());
+    }
+}
+
+/// Moves a player spawned before the level was loaded, or one that was there when
+/// the level changed, to the start of the new level.
+pub fn move_player_to_level_start(
+    level_start: Res<LevelStart>,
+    mut player_query: Query<&mut Position, With<Player>>,
+) {
+    if !level_start.is_changed() {
+        return;
+    }
+    let Some(start_position) = level_start.position else { return; };
+    for mut position in player_query.iter_mut() {
+        position.0 = start_position;
+    }
+}
+
+/// Keeps `LoadedLevel` up to date with the selected level whenever the project has
+/// been (re)loaded or another level selected.
+pub fn load_selected_level(
+    mut ldtk_event_reader: EventReader<AssetEvent<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    level_selection: Res<LevelSelection>,
+    world_query: Query<&Handle<LdtkAsset>>,
+    mut loaded_level: ResMut<LoadedLevel>,
+) {
+    let project_loaded = ldtk_event_reader
+        .iter()
+        .any(|event| matches!(event, AssetEvent::Created { .. } | AssetEvent::Modified { .. }));
+    if !project_loaded && !level_selection.is_changed() {
+        return;
+    }
+    let Some(level) = world_query
+        .iter()
+        .find_map(|handle| ldtk_assets.get(handle))
+        .and_then(|ldtk_asset| ldtk_asset.get_level(&level_selection)) else { return; };
+    loaded_level.level = Some(level.clone());
+}
+
+/*
+Every wall or water cell getting its own collider would leave the physics with
+thousands of boxes, and bullets catching on the seams between them. Whenever a
+level has been loaded, the colliders of the last one are thrown away and the cells
+of each layer are merged into as few rectangles as we can find.
+
+This works from the project data rather than the int cell entities LdtkPlugin
+spawns, so a headless simulation without it still gets its walls and water.
+ */
+pub fn build_level_terrain(
+    mut commands: Commands,
+    loaded_level: Res<LoadedLevel>,
+    level_settings: Res<LevelSettings>,
+    mut level_start: ResMut<LevelStart>,
+    terrain_collider_query: Query<Entity, Or<(With<WallCollider>, With<WaterArea>)>>,
+    in_water_query: Query<Entity, With<InWater>>,
+) {
+    if !loaded_level.is_changed() {
+        return;
+    }
+    let Some(level) = loaded_level.level.as_ref() else { return; };
+
+    for terrain_collider in terrain_collider_query.iter() {
+        commands.entity(terrain_collider).despawn();
+    }
+    // The water they were in is gone without them ever leaving it
+    for entity in in_water_query.iter() {
+        commands.entity(entity).remove::<InWater>();
+    }
+    for (center, size) in level_areas(level, level_settings.wall) {
+        commands.spawn(WallColliderBundle::new(center, size));
+    }
+    for (center, size) in level_areas(level, level_settings.water) {
+        commands.spawn(WaterAreaBundle::new(center, size));
+    }
+    level_start.position = level_start_position(level, level_settings.player_start);
+}
+
+/// Where the level and its layers are placed, in pixels, with `UseWorldTranslation`.
+fn layer_origin(level: &Level, layer: &LayerInstance) -> Vec2 {
+    ldtk_pixel_coords_to_translation(IVec2::new(level.world_x, level.world_y + level.px_hei), 0)
+        + Vec2::new(layer.px_total_offset_x as f32, -layer.px_total_offset_y as f32)
+}
+
+/// The cells of an int grid layer holding `value`, counted from the bottom left like `GridCoords`.
+fn layer_cells(layer: &LayerInstance, value: i32) -> HashSet<IVec2> {
+    layer.int_grid_csv
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| **cell == value)
+        .map(|(i, _)| IVec2::new(i as i32 % layer.c_wid, layer.c_hei - 1 - i as i32 / layer.c_wid))
+        .collect()
+}
+
+/// The centers and sizes, in the world, of the rectangles covering the cells holding `value`.
+fn level_areas(level: &Level, value: i32) -> Vec<(Vec2, Vec2)> {
+    let mut areas = Vec::new();
+    for layer in level.layer_instances.iter().flatten() {
+        let origin = layer_origin(level, layer);
+        let grid_size = layer.grid_size as f32;
+        for (min, max) in merge_cells(&layer_cells(layer, value)) {
+            let corner_a = origin + min.as_vec2() * grid_size;
+            let corner_b = origin + (max + IVec2::ONE).as_vec2() * grid_size;
+            areas.push(((corner_a + corner_b) / 2.0 * METERS_PER_PIXEL, (corner_b - corner_a) * METERS_PER_PIXEL));
+        }
+    }
+    areas
+}
+
+/// The center, in the world, of the top left cell holding `value`, if there is one.
+fn level_start_position(level: &Level, value: i32) -> Option<Vec2> {
+    level.layer_instances
+        .iter()
+        .flatten()
+        .find_map(|layer| {
+            let cell = layer_cells(layer, value).into_iter().min_by_key(|cell| (-cell.y, cell.x))?;
+            let cell_center = (cell.as_vec2() + 0.5) * layer.grid_size as f32;
+            Some((layer_origin(level, layer) + cell_center) * METERS_PER_PIXEL)
+        })
+}
+
+/// The "Pickup" entities placed in the level, with their centers in the world.
+pub fn level_pickups(level: &Level) -> Vec<(Pickup, Vec2)> {
+    level.layer_instances
+        .iter()
+        .flatten()
+        .flat_map(|layer| layer.entity_instances.iter().map(move |entity_instance| (layer, entity_instance)))
+        .filter(|(_, entity_instance)| entity_instance.identifier == "Pickup")
+        .map(|(layer, entity_instance)| {
+            let center = ldtk_pixel_coords_to_translation_pivoted(
+                entity_instance.px,
+                level.px_hei,
+                IVec2::new(entity_instance.width, entity_instance.height),
+                entity_instance.pivot,
+            );
+            (Pickup::from_entity_instance(entity_instance), (layer_origin(level, layer) + center) * METERS_PER_PIXEL)
+        })
+        .collect()
+}
+
+/// Greedily covers the cells with rectangles, given as their lower left and upper right cells.
+/// Runs are grown to the right first and then upwards for as long as the whole run fits.
+fn merge_cells(cells: &HashSet<IVec2>) -> Vec<(IVec2, IVec2)> {
+    let mut sorted = cells.iter().copied().collect::<Vec<_>>();
+    sorted.sort_by_key(|cell| (cell.y, cell.x));
+
+    let mut covered = HashSet::new();
+    let mut rectangles = Vec::new();
+    let free = |cell: IVec2, covered: &HashSet<IVec2>| cells.contains(&cell) && !covered.contains(&cell);
+    for min in sorted {
+        if covered.contains(&min) {
+            continue;
+        }
+        let mut max = min;
+        while free(IVec2::new(max.x + 1, min.y), &covered) {
+            max.x += 1;
+        }
+        while (min.x..=max.x).all(|x| free(IVec2::new(x, max.y + 1), &covered)) {
+            max.y += 1;
+        }
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                covered.insert(IVec2::new(x, y));
+            }
+        }
+        rectangles.push((min, max));
+    }
+    rectangles
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::{IVec2, Vec2};
+    use bevy::utils::HashSet;
+    use bevy_ecs_ldtk::ldtk::{LdtkJson, Level, Type};
+    use crate::components::pickup::Pickup;
+    use crate::plugins::level::LevelPlugin;
+    use super::{level_areas, level_pickups, level_start_position, merge_cells};
+
+    fn shipped_level() -> Level {
+        let project: LdtkJson = serde_json::from_str(include_str!("../../assets/levels/world.ldtk"))
+            .expect("assets/levels/world.ldtk should be a valid LDtk project");
+        let level = project
+            .iter_levels()
+            .find(|level| level.identifier == LevelPlugin::default().level)
+            .expect("the default level should be in the project")
+            .clone();
+        level
+    }
+
+    fn cells(cells: &[(i32, i32)]) -> HashSet<IVec2> {
+        cells.iter().map(|(x, y)| IVec2::new(*x, *y)).collect()
+    }
+
+    /// Every cell of the rectangles, failing if any of them overlap.
+    fn covered_cells(rectangles: &[(IVec2, IVec2)]) -> HashSet<IVec2> {
+        let mut covered = HashSet::new();
+        for (min, max) in rectangles {
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    assert!(covered.insert(IVec2::new(x, y)), "({}, {}) is covered twice", x, y);
+                }
+            }
+        }
+        covered
+    }
+
+    #[test]
+    fn single_cell_is_its_own_rectangle() {
+        let single = cells(&[(3, 4)]);
+        assert_eq!(merge_cells(&single), vec![(IVec2::new(3, 4), IVec2::new(3, 4))]);
+    }
+
+    #[test]
+    fn filled_rectangle_is_merged_into_one() {
+        let filled = cells(&[(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+        assert_eq!(merge_cells(&filled), vec![(IVec2::new(0, 0), IVec2::new(2, 1))]);
+    }
+
+    #[test]
+    fn l_shape_is_covered_by_two_rectangles() {
+        let l_shape = cells(&[(0, 0), (1, 0), (2, 0), (0, 1), (0, 2)]);
+        let rectangles = merge_cells(&l_shape);
+        assert_eq!(rectangles, vec![
+            (IVec2::new(0, 0), IVec2::new(2, 0)),
+            (IVec2::new(0, 1), IVec2::new(0, 2)),
+        ]);
+        assert_eq!(covered_cells(&rectangles), l_shape);
+    }
+
+    #[test]
+    fn hole_is_left_uncovered() {
+        let ring = cells(&[(0, 0), (1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let rectangles = merge_cells(&ring);
+        assert_eq!(rectangles.len(), 4);
+        assert_eq!(covered_cells(&rectangles), ring);
+    }
+
+    #[test]
+    fn shipped_level_has_terrain_a_player_start_and_pickups() {
+        let level = shipped_level();
+        let level_plugin = LevelPlugin::default();
+        let layers = level.layer_instances.as_ref().expect("levels should be saved with the project");
+
+        let int_cells = layers
+            .iter()
+            .filter(|layer| layer.layer_instance_type == Type::IntGrid)
+            .flat_map(|layer| layer.int_grid_csv.iter())
+            .collect::<Vec<_>>();
+        assert!(int_cells.contains(&&level_plugin.wall));
+        assert!(int_cells.contains(&&level_plugin.water));
+        assert_eq!(int_cells.iter().filter(|cell| ***cell == level_plugin.player_start).count(), 1);
+
+        let pickups = level_pickups(&level);
+        assert!(pickups.iter().any(|(pickup, _)| matches!(pickup, Pickup::Weapon(weapon) if weapon == "Shotgun")));
+        assert!(pickups.iter().any(|(pickup, _)| matches!(pickup, Pickup::Health(50))));
+        // The level spans -224..224 by -128..128 meters, pickups are placed inside it
+        assert!(pickups.iter().all(|(_, position)| position.abs().cmplt(Vec2::new(224.0, 128.0)).all()));
+        assert!(pickups.iter().any(|(_, position)| *position == Vec2::new(13.0, 3.0)));
+    }
+
+    #[test]
+    fn shipped_level_is_built_around_the_origin() {
+        let level = shipped_level();
+        let level_plugin = LevelPlugin::default();
+
+        // The start is the cell just below and to the right of the middle of the level
+        assert_eq!(level_start_position(&level, level_plugin.player_start), Some(Vec2::new(1.0, -1.0)));
+
+        let walls = level_areas(&level, level_plugin.wall);
+        let left = walls.iter().map(|(center, size)| center.x - size.x / 2.0).fold(f32::MAX, f32::min);
+        let top = walls.iter().map(|(center, size)| center.y + size.y / 2.0).fold(f32::MIN, f32::max);
+        assert_eq!((left, top), (-224.0, 128.0));
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::{Entity, EventWriter, Query, Res, ResMut, Time, With, Without};
+use bevy_rand::prelude::GlobalEntropy;
+use bevy_xpbd_2d::components::Position;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use crate::boids::components::{Boid, Knockback};
+use crate::components::control::{PlayerControl, TriggerPulled};
+use crate::components::effects::StatusEffects;
+use crate::components::general::Health;
+use crate::components::player::Dead;
+use crate::components::quad::QuadStore;
+use crate::components::weapon::CurrentWeapon;
+use crate::events::damage::DamageEvent;
+
+/// Swings the current melee weapon, hitting every boid inside the arc in front of where we aim.
+/// The swing cools down whether or not the trigger is held, semi-auto weapons swing once per pull.
+pub fn melee_attack_system(
+    time: Res<Time>,
+    mut attacker_query: Query<(Entity, &Position, &mut CurrentWeapon, &PlayerControl, Option<&TriggerPulled>), Without<Dead>>,
+    mut boid_query: Query<(&Position, &mut Health, &mut Knockback, &mut StatusEffects), With<Boid>>,
+    quad_store: Res<QuadStore>,
+    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    mut damage_event_writer: EventWriter<DamageEvent>,
+) {
+    for (attacker, attacker_position, mut current_weapon, player_control, trigger_pulled) in attacker_query.iter_mut() {
+        if !current_weapon.is_melee() {
+            continue;
+        }
+
+        current_weapon.tick(time.delta_seconds());
+
+        if trigger_pulled.is_none() {
+            current_weapon.release_trigger();
+            if !current_weapon.is_bursting() {
+                continue;
+            }
+        }
+
+        if current_weapon.did_we_fire() {
+            let Some(weapon) = current_weapon.weapon.as_ref() else { continue; };
+            let half_arc = weapon.melee_arc.to_radians() / 2.0;
+            for boid in quad_store.entities_within(attacker_position.0, weapon.melee_range) {
+                if let Ok((boid_position, mut health, mut knockback, mut status_effects)) = boid_query.get_mut(boid) {
+                    let delta = boid_position.0 - attacker_position.0;
+                    if health.health <= 0 || delta.length() > weapon.melee_range || player_control.aim_direction.angle_between(delta).abs() > half_arc {
+                        continue;
+                    }
+                    let amount = rng.gen_range(weapon.effective_damage());
+                    health.health -= amount;
+                    damage_event_writer.send(DamageEvent {
+                        shooter: attacker,
+                        target: boid,
+                        amount,
+                        weapon: weapon.name.clone(),
+                    });
+                    if health.health > 0 {
+                        for effect in weapon.hit_effects().iter() {
+                            status_effects.apply(effect, attacker);
+                        }
+                        if !status_effects.is_knockback_immune() {
+                            knockback.push(delta, weapon.knockback);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
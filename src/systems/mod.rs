@@ -1,9 +1,20 @@
-pub(crate) mod input;
-pub(crate) mod startup;
-pub(crate) mod camera;
-pub(crate) mod movement;
-pub(crate) mod shooting;
-pub(crate) mod player;
-pub(crate) mod collisions;
-pub(crate) mod quads;
+pub mod input;
+pub mod startup;
+pub mod camera;
+pub mod movement;
+pub mod shooting;
+pub mod player;
+pub mod collisions;
+pub mod quads;
+pub mod sprites;
 
+pub mod weapons;
+pub mod explosions;
+pub mod melee;
+pub mod projectiles;
+pub mod pickups;
+pub mod ui;
+pub mod game;
+pub mod effects;
+pub mod level;
+pub mod terrain;
@@ -1,7 +1,10 @@
-use bevy::prelude::{Query, With, Without};
-use bevy_xpbd_2d::components::{ExternalForce, LinearVelocity};
-use crate::boids::components::{Boid, BoidDirection};
+use bevy::prelude::{EventReader, Or, Query, With, Without};
+use bevy_xpbd_2d::components::{ExternalForce, LinearVelocity, Position};
+use bevy_xpbd_2d::prelude::{Collision, Contact};
+use crate::boids::components::{Boid, BoidDirection, Knockback};
 use crate::components::control::PlayerControl;
+use crate::components::effects::StatusEffects;
+use crate::components::general::WallCollider;
 use crate::components::player::Player;
 
 #[allow(dead_code)]
@@ -13,21 +16,59 @@ fn external_force_player_control(
     }
 }
 
+/// Slowed boids move slower and stunned boids not at all, boids that were knocked back fly
+/// where the hit sent them until the knockback has decayed.
 pub fn linear_velocity_control_boid(
-    mut query: Query<(&mut LinearVelocity, &BoidDirection), (With<Boid>, Without<Player>)>
+    mut query: Query<(&mut LinearVelocity, &BoidDirection, &StatusEffects, &Knockback), (With<Boid>, Without<Player>)>
 ) {
-    let mut iter = query.iter_mut();
-    while let Some((mut linear_velocity, direction_control)) = iter.next() {
-        linear_velocity.x = direction_control.direction.x * direction_control.force_scale;
-        linear_velocity.y = direction_control.direction.y * direction_control.force_scale;
+    for (mut linear_velocity, direction_control, status_effects, knockback) in query.iter_mut() {
+        if knockback.is_active() {
+            linear_velocity.0 = knockback.velocity;
+            continue;
+        }
+        let speed = direction_control.force_scale * status_effects.speed_factor();
+        linear_velocity.x = direction_control.direction.x * speed;
+        linear_velocity.y = direction_control.direction.y * speed;
     }
 }
 
 pub fn linear_velocity_control_player(
-    mut query: Query<(&mut LinearVelocity, &PlayerControl), (Without<Boid>, With<Player>)>
+    mut query: Query<(&mut LinearVelocity, &PlayerControl, &StatusEffects), (Without<Boid>, With<Player>)>
 ) {
-    if let Ok((mut linear_velocity, direction_control)) = query.get_single_mut() {
-        linear_velocity.x = direction_control.direction.x * direction_control.force_scale;
-        linear_velocity.y = direction_control.direction.y * direction_control.force_scale;
+    if let Ok((mut linear_velocity, direction_control, status_effects)) = query.get_single_mut() {
+        let speed = direction_control.force_scale * status_effects.speed_factor();
+        linear_velocity.x = direction_control.direction.x * speed;
+        linear_velocity.y = direction_control.direction.y * speed;
     }
 }
+
+/*
+The player and the boids are kinematic, the physics reports them touching walls
+but doesn't stop them, so we push them back out along the contact normal.
+ */
+pub fn push_out_of_walls(
+    mut collision_reader: EventReader<Collision>,
+    wall_query: Query<&Position, With<WallCollider>>,
+    mut body_query: Query<&mut Position, (Or<(With<Player>, With<Boid>)>, Without<WallCollider>)>,
+) {
+    for Collision(contact) in collision_reader.iter() {
+        let (wall, body) = if wall_query.contains(contact.entity1) {
+            (contact.entity1, contact.entity2)
+        } else {
+            (contact.entity2, contact.entity1)
+        };
+        let Ok(wall_position) = wall_query.get(wall) else { continue; };
+        let Ok(mut body_position) = body_query.get_mut(body) else { continue; };
+        push_away(&mut body_position, wall_position, contact);
+    }
+}
+
+/// Moves the body out of the obstacle it overlaps, along the contact normal pointing away from the obstacle.
+pub fn push_away(body_position: &mut Position, obstacle_position: &Position, contact: &Contact) {
+    let normal = if (body_position.0 - obstacle_position.0).dot(contact.normal) < 0.0 {
+        -contact.normal
+    } else {
+        contact.normal
+    };
+    body_position.0 += normal * contact.penetration;
+}
@@ -0,0 +1,117 @@
+use bevy::log::warn;
+use bevy::math::{Vec2, Vec3};
+use bevy::prelude::{Commands, Entity, EventReader, Query, Res, ResMut, Transform, TransformBundle, With};
+use bevy::utils::HashSet;
+use bevy_rand::prelude::GlobalEntropy;
+use bevy_xpbd_2d::prelude::CollisionStarted;
+use rand_chacha::ChaCha8Rng;
+use crate::components::general::{Health, SpriteTexture};
+use crate::components::level::LoadedLevel;
+use crate::components::pickup::{Dropped, LootTable, Pickup, PickupBundle, Placed};
+use crate::components::player::{Player, WeaponInventory};
+use crate::components::weapon::{CurrentWeapon, WeaponDefs};
+use crate::events::damage::BoidDiedEvent;
+use crate::systems::level::level_pickups;
+use crate::METERS_PER_PIXEL;
+
+pub fn spawn_pickup(commands: &mut Commands, pickup: Pickup, position: Vec2) -> Entity {
+    commands.spawn((
+        PickupBundle::new(pickup, position),
+        TransformBundle::from_transform(
+            Transform::from_xyz(
+                position.x,
+                position.y,
+                0.5,
+            )
+                .with_scale(Vec3::new(
+                    METERS_PER_PIXEL,
+                    METERS_PER_PIXEL,
+                    1.0,
+                ))),
+        SpriteTexture("sprites/pickup.png"),
+    )).id()
+}
+
+pub fn pickup_system(
+    mut collision_event_reader: EventReader<CollisionStarted>,
+    pickup_query: Query<&Pickup>,
+    mut player_query: Query<(&mut WeaponInventory, &mut CurrentWeapon, &mut Health), With<Player>>,
+    weapon_defs: Res<WeaponDefs>,
+    mut commands: Commands,
+) {
+    let mut collected = HashSet::new();
+    for CollisionStarted(entity1, entity2) in collision_event_reader.iter() {
+        let (pickup_entity, player_entity) = if pickup_query.contains(*entity1) {
+            (*entity1, *entity2)
+        } else {
+            (*entity2, *entity1)
+        };
+        let Ok(pickup) = pickup_query.get(pickup_entity) else { continue; };
+        let Ok((mut weapon_inventory, mut current_weapon, mut health)) = player_query.get_mut(player_entity) else { continue; };
+        if !collected.insert(pickup_entity) {
+            continue;
+        }
+
+        match pickup {
+            Pickup::Weapon(name) => {
+                if let Some(weapon_def) = weapon_defs.defs.iter().find(|weapon_def| weapon_def.name == *name) {
+                    weapon_inventory.add_weapon(&mut current_weapon, weapon_def);
+                } else {
+                    warn!("Picked up unknown weapon {}", name);
+                }
+            }
+            Pickup::Ammo { weapon, amount } => {
+                let weapon = match weapon {
+                    Some(name) => weapon_inventory.find_weapon_mut(&mut current_weapon, name),
+                    None => current_weapon.weapon.as_mut(),
+                };
+                if let Some(weapon) = weapon {
+                    weapon.add_ammo(*amount);
+                }
+            }
+            Pickup::Health(amount) => {
+                health.health = (health.health + amount).min(health.max);
+            }
+        }
+        commands.entity(pickup_entity).despawn();
+    }
+}
+
+pub fn drop_loot_from_boids(
+    mut boid_died_reader: EventReader<BoidDiedEvent>,
+    loot_table: Res<LootTable>,
+    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    mut commands: Commands,
+) {
+    for BoidDiedEvent { position, .. } in boid_died_reader.iter() {
+        for pickup in loot_table.roll(&mut *rng) {
+            let pickup = spawn_pickup(&mut commands, pickup, *position);
+            commands.entity(pickup).insert(Dropped);
+        }
+    }
+}
+
+pub fn despawn_dropped_pickups(
+    pickup_query: Query<Entity, With<Dropped>>,
+    mut commands: Commands,
+) {
+    for pickup in pickup_query.iter() {
+        commands.entity(pickup).despawn();
+    }
+}
+
+/// Puts back the pickups placed in the level, whatever was collected of them last time.
+pub fn spawn_level_pickups(
+    loaded_level: Res<LoadedLevel>,
+    pickup_query: Query<Entity, With<Placed>>,
+    mut commands: Commands,
+) {
+    for pickup in pickup_query.iter() {
+        commands.entity(pickup).despawn();
+    }
+    let Some(level) = loaded_level.level.as_ref() else { return; };
+    for (pickup, position) in level_pickups(level) {
+        let pickup = spawn_pickup(&mut commands, pickup, position);
+        commands.entity(pickup).insert(Placed);
+    }
+}
@@ -1,63 +1,139 @@
-use bevy::prelude::{Commands, default, Entity, Query, Res, SpriteBundle, Transform};
-use bevy::asset::AssetServer;
-use bevy::math::Vec3;
-use crate::components::control::{CycleDirection, CycleWeapon};
-use crate::components::player::{PlayerBundle, WeaponInventory};
-use crate::components::weapon::{CurrentWeapon, WeaponDefs};
-use crate::METERS_PER_PIXEL;
-
-pub fn cycle_weapon_system(
+use bevy::prelude::{Commands, Entity, EventWriter, NextState, Or, Query, Res, ResMut, Time, Transform, TransformBundle, With, Without};
+use bevy::math::{Vec2, Vec3};
+use crate::components::control::{CycleWeapon, PlayerControl, PlayerScript, SelectWeapon, TriggerPulled};
+use bevy_xpbd_2d::components::Position;
+use crate::components::general::{Health, SpriteTexture};
+use crate::components::level::LevelStart;
+use crate::components::player::{Dead, Player, PlayerBundle, PlayerSettings, WeaponInventory};
+use crate::components::weapon::{CurrentWeapon, WeaponDefs, WeaponSwitchSettings};
+use crate::events::damage::PlayerDiedEvent;
+use crate::events::weapons::WeaponSwitchedEvent;
+use crate::{GameState, METERS_PER_PIXEL};
+
+pub fn switch_weapon_system(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut WeaponInventory, &mut CurrentWeapon, &CycleWeapon)>,
+    switch_settings: Res<WeaponSwitchSettings>,
+    mut query: Query<(Entity, &mut WeaponInventory, &mut CurrentWeapon, Option<&CycleWeapon>, Option<&SelectWeapon>), Or<(With<CycleWeapon>, With<SelectWeapon>)>>,
+    mut weapon_switched_writer: EventWriter<WeaponSwitchedEvent>,
 ) {
-    for (entity, mut weapon_inventory, mut current_weapon, cycle) in query.iter_mut() {
-        match &current_weapon.weapon {
-            Some(weapon) => weapon_inventory.weapons.push_back(weapon.clone()),
-            None => {}
-        }
-        match cycle.direction{
-            CycleDirection::Forward => {
-                if let Some(new_weapon) = weapon_inventory.weapons.pop_front() {
-                    current_weapon.set_weapon(new_weapon.clone());
-                }
-            }
-            CycleDirection::Backward => {
-                weapon_inventory.weapons.rotate_left(1);
-                if let Some(new_weapon) = weapon_inventory.weapons.pop_front() {
-                    current_weapon.set_weapon(new_weapon.clone());
-                }
+    for (entity, mut weapon_inventory, mut current_weapon, cycle, select) in query.iter_mut() {
+        let from = current_weapon.weapon.as_ref().map(|weapon| weapon.name.clone());
+        let switched = match (select, cycle) {
+            (Some(select), _) => weapon_inventory.select_slot(&mut current_weapon, select.slot),
+            (None, Some(cycle)) => weapon_inventory.cycle(&mut current_weapon, &cycle.direction),
+            (None, None) => false,
+        };
+        if switched {
+            current_weapon.time_to_next_shot = switch_settings.delay;
+            if let Some(weapon) = current_weapon.weapon.as_ref() {
+                weapon_switched_writer.send(WeaponSwitchedEvent {
+                    shooter: entity,
+                    from,
+                    to: weapon.name.clone(),
+                });
             }
         }
 
-        commands.entity(entity).remove::<CycleWeapon>();
+        commands.entity(entity).remove::<(CycleWeapon, SelectWeapon)>();
+    }
+}
+
+pub fn scripted_player_control(
+    time: Res<Time>,
+    mut script: ResMut<PlayerScript>,
+    mut query: Query<(Entity, &Position, &mut PlayerControl), (With<Player>, Without<Dead>)>,
+    mut commands: Commands,
+) {
+    if script.steps.is_empty() {
+        return;
+    }
+    let Ok((entity, position, mut player_control)) = query.get_single_mut() else { return; };
+
+    script.time_left -= time.delta_seconds();
+    if script.time_left <= 0.0 {
+        script.current = (script.current + 1) % script.steps.len();
+        script.time_left = script.steps[script.current].duration;
+    }
+
+    let step = script.steps[script.current].clone();
+    player_control.direction = step.direction.normalize_or_zero();
+    // mouse_look derives the aim from the mouse position, so we aim by moving the "mouse"
+    player_control.mouse_position = position.0 + step.aim_direction;
+    if step.trigger_pulled {
+        commands.entity(entity).insert(TriggerPulled {});
+    } else {
+        commands.entity(entity).remove::<TriggerPulled>();
     }
 }
 
+/// Spawns the player at the start of the level, or at the configured start position
+/// if there is no level or it has no start.
 pub fn spawn_player(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    player_settings: Res<PlayerSettings>,
+    level_start: Option<Res<LevelStart>>,
     weapon_definitions: Res<WeaponDefs>) {
+    let start_position = level_start
+        .and_then(|level_start| level_start.position)
+        .unwrap_or(player_settings.start_position);
+    let mut current_weapon = CurrentWeapon::default();
+    let mut weapon_inventory = WeaponInventory::new(weapon_definitions.defs.iter().map(|def| def.create_weapon()));
+    weapon_inventory.select_slot(&mut current_weapon, 0);
     commands
         .spawn((
-            SpriteBundle {
-                transform: Transform::from_xyz(
-                    0.0,
-                    0.0,
+            TransformBundle::from_transform(
+                Transform::from_xyz(
+                    start_position.x,
+                    start_position.y,
                     1.0,
                 )
                     .with_scale(Vec3::new(
                         METERS_PER_PIXEL,
                         METERS_PER_PIXEL,
                         1.0,
-                    )),
-                texture: asset_server.load("sprites/person.png"),
-                ..default()
-            },
+                    ))),
+            SpriteTexture("sprites/person.png"),
             PlayerBundle {
-                weapon_inventory: WeaponInventory {
-                    weapons: weapon_definitions.defs.iter().map(|def| def.create_weapon()).collect(),
-                },
+                position: Position::from(start_position),
+                weapon_inventory,
+                current_weapon,
                 ..Default::default()
             },
         ));
 }
+
+/// Replaces the player with a fresh one for a new game.
+pub fn respawn_player(
+    mut commands: Commands,
+    player_query: Query<Entity, With<Player>>,
+    player_settings: Res<PlayerSettings>,
+    level_start: Option<Res<LevelStart>>,
+    weapon_definitions: Res<WeaponDefs>,
+) {
+    for player in player_query.iter() {
+        commands.entity(player).despawn();
+    }
+    spawn_player(commands, player_settings, level_start, weapon_definitions);
+}
+
+/// A player that runs out of health dies, stops moving and shooting, and the game is over.
+pub fn player_death_system(
+    mut query: Query<(Entity, &mut Health, &mut PlayerControl), (With<Player>, Without<Dead>)>,
+    mut player_died_writer: EventWriter<PlayerDiedEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut commands: Commands,
+) {
+    for (player, mut health, mut player_control) in query.iter_mut() {
+        if health.health > 0 {
+            continue;
+        }
+        health.health = 0;
+        player_control.direction = Vec2::ZERO;
+        commands
+            .entity(player)
+            .insert(Dead {})
+            .remove::<TriggerPulled>();
+        player_died_writer.send(PlayerDiedEvent { player });
+        next_state.set(GameState::GameOver);
+    }
+}
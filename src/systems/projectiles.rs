@@ -0,0 +1,33 @@
+use bevy::prelude::{Commands, Entity, EventWriter, Query, Res, Time, With};
+use bevy_xpbd_2d::components::Position;
+use crate::components::weapon::{Explosive, Projectile, ProjectileDamage, ProjectileLifetime, Shooter};
+use crate::events::explosions::ExplosionEvent;
+
+/// Removes projectiles that have flown past their weapon's range or lifetime.
+/// Rockets and grenades detonate where they end up.
+pub fn projectile_lifetime_system(
+    time: Res<Time>,
+    mut projectile_query: Query<(Entity, &Position, &mut ProjectileLifetime, &ProjectileDamage, &Shooter, Option<&Explosive>)>,
+    mut explosion_event_writer: EventWriter<ExplosionEvent>,
+    mut commands: Commands,
+) {
+    for (projectile, position, mut lifetime, damage, shooter, explosive) in projectile_query.iter_mut() {
+        lifetime.time_left -= time.delta_seconds();
+        if !lifetime.is_spent(position.0) {
+            continue;
+        }
+        if let Some(explosive) = explosive {
+            explosion_event_writer.send(ExplosionEvent::from_projectile(position.0, explosive, damage, shooter.0));
+        }
+        commands.entity(projectile).despawn();
+    }
+}
+
+pub fn despawn_projectiles(
+    projectile_query: Query<Entity, With<Projectile>>,
+    mut commands: Commands,
+) {
+    for projectile in projectile_query.iter() {
+        commands.entity(projectile).despawn();
+    }
+}
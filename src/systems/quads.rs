@@ -163,9 +163,8 @@
 //     }
 // }
 
-use bevy::log::info;
-use bevy::prelude::{Entity, Query, ResMut};
-use bevy::utils::HashSet;
+use bevy::log::{info, warn};
+use bevy::prelude::{Entity, Query, RemovedComponents, Res, ResMut};
 use bevy_xpbd_2d::components::Position;
 use crate::components::quad::{QuadCoord, QuadStore, Rebuild};
 
@@ -173,7 +172,6 @@ pub fn naive_quad_system(
     mut query: Query<(Entity, &Position, &mut QuadCoord)>,
     quad_store: ResMut<QuadStore>,
 ) {
-    let mut iter = query.iter_mut();
     let quad_store = quad_store.into_inner();
     match quad_store.rebuild_store {
         Rebuild::KeepQuadSize => {
@@ -185,6 +183,7 @@ pub fn naive_quad_system(
             } else {
                 info!("Shrinking quad size");
                 quad_store.entities.clear();
+                quad_store.coords.clear();
                 quad_store.quad_size = (quad_store.quad_size / 2.0).clamp(quad_store.min_quad_size, quad_store.max_quad_size);
                 quad_store.rebuild_store = Rebuild::KeepQuadSize;
                 info!("Size: {:.2}", quad_store.quad_size);
@@ -196,47 +195,26 @@ pub fn naive_quad_system(
             } else {
                 info!("Growing quad size");
                 quad_store.entities.clear();
+                quad_store.coords.clear();
                 quad_store.quad_size = (quad_store.quad_size * 2.0).clamp(quad_store.min_quad_size, quad_store.max_quad_size);
                 quad_store.rebuild_store = Rebuild::KeepQuadSize;
                 info!("Size: {:.2}", quad_store.quad_size);
             }
         }
     }
-    while let Some((entity, position, mut quad_coord)) = iter.next() {
+    for (entity, position, mut quad_coord) in query.iter_mut() {
         let new_coord = QuadCoord::new(
             (position.0.x / quad_store.quad_size).floor() as i32,
             (position.0.y / quad_store.quad_size).floor() as i32,
         );
 
-        if !new_coord.eq(&quad_coord) {
-            if !quad_store.entities.contains_key(&new_coord) {
-                quad_store.entities.insert(new_coord, HashSet::new());
-            }
-            let old_coord = quad_coord.clone();
-            if let Some(old_set) = quad_store.entities.get_mut(&old_coord) {
-                old_set.remove(&entity);
-                if old_set.is_empty() {
-                    quad_store.entities.remove(&old_coord);
-                }
-            }
-            // if quad_store.entities.contains_key(&old_coord) {
-            //     let set = quad_store.entities.get_mut(&old_coord).unwrap();
-            //     set.remove(&entity);
-            //     if set.is_empty() {
-            //         quad_store.entities.remove(&old_coord);
-            //     }
-            // }
-
-            if let Some(set) = quad_store.entities.get_mut(&new_coord) {
-                set.insert(entity);
-                if set.len() > quad_store.largest_count {
-                    quad_store.largest_count = set.len();
-                }
-            } else {
-                quad_store.entities.insert(new_coord, HashSet::new());
-                quad_store.entities.get_mut(&new_coord).unwrap().insert(entity);
-            }
-
+        /*
+        Entities are also missing from the store when it was just cleared for a new
+        quad size, or when they were spawned in the quad their `QuadCoord` already
+        said, so it is not enough to look for changed coordinates.
+         */
+        if !new_coord.eq(&quad_coord) || !quad_store.contains(entity) {
+            quad_store.insert(entity, new_coord);
             quad_coord.x = new_coord.x;
             quad_coord.y = new_coord.y;
         }
@@ -246,4 +224,27 @@ pub fn naive_quad_system(
     } else if quad_store.largest_count < quad_store.min_entities {
         quad_store.rebuild_store = Rebuild::GrowQuadSize;
     }
-}
\ No newline at end of file
+}
+pub fn clear_quad_store(mut quad_store: ResMut<QuadStore>) {
+    quad_store.clear();
+}
+
+/// Takes entities out of the store when they are despawned or lose their `QuadCoord`.
+pub fn remove_from_quad_store(
+    mut removed: RemovedComponents<QuadCoord>,
+    mut quad_store: ResMut<QuadStore>,
+) {
+    for entity in removed.iter() {
+        quad_store.remove(entity);
+    }
+}
+
+/// Warns about everything in the store that doesn't match the entities with a `QuadCoord`.
+pub fn check_quad_store(
+    query: Query<(Entity, &QuadCoord)>,
+    quad_store: Res<QuadStore>,
+) {
+    for inconsistency in quad_store.inconsistencies(query.iter().map(|(entity, coord)| (entity, *coord))) {
+        warn!("QuadStore is inconsistent: {:?}", inconsistency);
+    }
+}
@@ -1,50 +1,126 @@
-use bevy::asset::AssetServer;
-use bevy::math::Vec3;
-use bevy::prelude::{Commands, default, Entity, Query, Res, Time, Transform, With};
-use bevy::sprite::SpriteBundle;
+use bevy::math::{Vec2, Vec3};
+use bevy::prelude::{Commands, Entity, EventWriter, Query, Res, ResMut, Time, Transform, TransformBundle, Without};
+use bevy_rand::prelude::GlobalEntropy;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
 use bevy_xpbd_2d::components::Position;
 use bevy_xpbd_2d::prelude::{Collider, CollisionLayers};
 use crate::components::control::TriggerPulled;
 use crate::components::control::PlayerControl;
-use crate::components::weapon::{CurrentWeapon, ProjectileBundle};
+use crate::components::general::SpriteTexture;
+use crate::components::player::Dead;
+use crate::components::weapon::{AmmoType, CurrentWeapon, Explosive, Fuse, Piercing, ProjectileBundle, ProjectileDamage, ProjectileLifetime, Ricochet};
+use crate::events::ammo::OutOfAmmoEvent;
 use crate::{Layer, METERS_PER_PIXEL};
 
+/*
+Every shot fires the weapon's pellets, each rolling its own damage and its own angle
+within the spread cone. Bursts keep firing after the trigger is released.
+ */
 pub fn shooting_system(
     time: Res<Time>,
-    mut shooter_query: Query<(Entity, &Position, &mut CurrentWeapon, &PlayerControl), With<TriggerPulled>>,
+    mut shooter_query: Query<(Entity, &Position, &mut CurrentWeapon, &PlayerControl, Option<&TriggerPulled>), Without<Dead>>,
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    mut out_of_ammo_writer: EventWriter<OutOfAmmoEvent>,
 ) {
-    for (shooter, shooter_position, mut current_weapon, player_control)
+    for (shooter, shooter_position, mut current_weapon, player_control, trigger_pulled)
     in shooter_query.iter_mut() {
+        if current_weapon.is_melee() {
+            continue;
+        }
 
         current_weapon.tick(time.delta_seconds());
 
+        if trigger_pulled.is_none() {
+            current_weapon.release_trigger();
+            if !current_weapon.is_bursting() {
+                continue;
+            }
+        }
+
         if current_weapon.did_we_fire() {
-            commands.spawn((
-                ProjectileBundle::new(
-                    "Bullet".to_string(),
-                    *shooter_position,
-                    player_control.aim_direction * current_weapon.bullet_speed_or_zero(),
-                    Collider::ball(0.5),
-                    CollisionLayers::new([Layer::Bullet], [Layer::Player, Layer::Boid]),
-                    shooter,
-                ),
-                SpriteBundle {
-                    transform: Transform::from_xyz(
-                        shooter_position.x,
-                        shooter_position.y,
-                        1.0,
-                    )
-                        .with_scale(Vec3::new(
-                            METERS_PER_PIXEL,
-                            METERS_PER_PIXEL,
+            let Some(weapon) = current_weapon.weapon.as_ref() else { continue; };
+            let explosive = Explosive {
+                radius: weapon.explosion_radius,
+                force: weapon.explosion_force,
+            };
+            // Grenades fly over the boids, everything else hits them
+            let (name, collision_layers) = match &weapon.ammo_type {
+                AmmoType::Bullet(name) => (name.clone(), CollisionLayers::new([Layer::Bullet], [Layer::Player, Layer::Boid, Layer::Walls, Layer::Water])),
+                AmmoType::Rocket(name) => (name.clone(), CollisionLayers::new([Layer::Bullet], [Layer::Player, Layer::Boid, Layer::Walls, Layer::Water])),
+                AmmoType::Grenade(name) => (name.clone(), CollisionLayers::new([Layer::Bullet], [Layer::Walls, Layer::Water])),
+            };
+            for _ in 0..weapon.effective_pellets() {
+                let damage = ProjectileDamage {
+                    amount: rng.gen_range(weapon.effective_damage()),
+                    weapon: weapon.name.clone(),
+                    effects: weapon.hit_effects(),
+                    knockback: weapon.knockback,
+                };
+                let lifetime = ProjectileLifetime {
+                    origin: shooter_position.0,
+                    max_range: weapon.max_range,
+                    time_left: weapon.lifetime,
+                };
+                let half_spread = weapon.effective_spread().to_radians() / 2.0;
+                let direction = if half_spread > 0.0 {
+                    Vec2::from_angle(rng.gen_range(-half_spread..=half_spread)).rotate(player_control.aim_direction)
+                } else {
+                    player_control.aim_direction
+                };
+                let mut projectile = commands.spawn((
+                    ProjectileBundle::new(
+                        name.clone(),
+                        *shooter_position,
+                        direction * weapon.effective_bullet_speed(),
+                        Collider::ball(0.5),
+                        collision_layers,
+                        shooter,
+                        damage,
+                        lifetime,
+                    ),
+                    TransformBundle::from_transform(
+                        Transform::from_xyz(
+                            shooter_position.x,
+                            shooter_position.y,
                             1.0,
-                        )),
-                    texture: asset_server.load("sprites/bullet.png"),
-                    ..default()
-                },
-            ));
+                        )
+                            .with_scale(Vec3::new(
+                                METERS_PER_PIXEL,
+                                METERS_PER_PIXEL,
+                                1.0,
+                            ))),
+                    SpriteTexture("sprites/bullet.png"),
+                ));
+                match &weapon.ammo_type {
+                    AmmoType::Bullet(_) => {
+                        if weapon.effective_piercing() > 0 {
+                            projectile.insert(Piercing {
+                                boids_left: weapon.effective_piercing(),
+                                damage_kept: weapon.pierce_damage,
+                            });
+                        }
+                        if weapon.effective_bounces() > 0 {
+                            projectile.insert(Ricochet {
+                                bounces_left: weapon.effective_bounces(),
+                            });
+                        }
+                    }
+                    AmmoType::Rocket(_) => {
+                        projectile.insert(explosive.clone());
+                    }
+                    AmmoType::Grenade(_) => {
+                        projectile.insert((explosive.clone(), Fuse { time_left: weapon.fuse }));
+                    }
+                }
+            }
+            if weapon.is_out_of_ammo() {
+                out_of_ammo_writer.send(OutOfAmmoEvent {
+                    shooter,
+                    weapon: weapon.name.clone(),
+                });
+            }
         }
     }
-}
\ No newline at end of file
+}
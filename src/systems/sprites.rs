@@ -0,0 +1,19 @@
+use bevy::asset::{AssetServer, Handle};
+use bevy::prelude::{Added, Commands, ComputedVisibility, Entity, Image, Query, Res, Sprite, Visibility};
+use crate::components::general::SpriteTexture;
+
+pub fn attach_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    query: Query<(Entity, &SpriteTexture), Added<SpriteTexture>>,
+) {
+    for (entity, sprite_texture) in query.iter() {
+        let texture: Handle<Image> = asset_server.load(sprite_texture.0);
+        commands.entity(entity).insert((
+            Sprite::default(),
+            texture,
+            Visibility::default(),
+            ComputedVisibility::default(),
+        ));
+    }
+}
@@ -1,33 +1,9 @@
-use bevy::prelude::{Camera2dBundle, Commands, default, OrthographicProjection, Res, SpriteBundle, Transform};
-use bevy::asset::AssetServer;
-use bevy::math::{Rect, Vec2, Vec3};
+use bevy::prelude::{Camera2dBundle, Commands, default, OrthographicProjection, Transform};
+use bevy::math::{Rect, Vec2};
 use bevy::core::Name;
 use bevy::render::camera::ScalingMode;
 use crate::components::general::GameCam;
-use crate::{CAMERA_SCALE, METERS_PER_PIXEL, PIXELS_PER_METER};
-
-pub fn load_background(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-) {
-    commands
-        .spawn((
-            SpriteBundle {
-                transform: Transform::from_xyz(
-                    0.0,
-                    0.0,
-                    0.0,
-                )
-                    .with_scale(Vec3::new(
-                        METERS_PER_PIXEL * 10.0,
-                        METERS_PER_PIXEL * 10.0,
-                        1.0,
-                    )),
-                texture: asset_server.load("background/background.png"),
-                ..default()
-            },
-        ));
-}
+use crate::{CAMERA_SCALE, PIXELS_PER_METER};
 
 pub fn spawn_camera(mut commands: Commands) {
     commands.spawn((
@@ -0,0 +1,139 @@
+use bevy::asset::{AssetEvent, AssetServer, Assets};
+use bevy::log::info;
+use bevy::prelude::{Commands, Entity, EventReader, Or, Query, Res, ResMut, With, Without};
+use bevy::utils::HashMap;
+use bevy_xpbd_2d::components::{CollidingEntities, LinearVelocity, Position};
+use bevy_xpbd_2d::prelude::{Collision, CollisionEnded, CollisionStarted};
+use crate::boids::components::Boid;
+use crate::components::general::{InWater, WaterArea};
+use crate::components::level::{Terrain, TerrainFile};
+use crate::components::player::Player;
+use crate::components::weapon::Projectile;
+use crate::systems::movement::push_away;
+
+pub fn load_terrain(
+    asset_server: Res<AssetServer>,
+    mut terrain_file: ResMut<TerrainFile>,
+) {
+    let handle = asset_server.load(terrain_file.path.as_str());
+    terrain_file.handle = handle;
+}
+
+/// Replaces `Terrain` whenever the terrain file is loaded or hot reloaded.
+pub fn apply_terrain(
+    mut asset_events: EventReader<AssetEvent<Terrain>>,
+    terrain_file: Res<TerrainFile>,
+    terrain_assets: Res<Assets<Terrain>>,
+    mut terrain: ResMut<Terrain>,
+) {
+    for event in asset_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } if *handle == terrain_file.handle => {
+                if let Some(terrain_asset) = terrain_assets.get(handle) {
+                    info!("Loaded terrain from {}", terrain_file.path);
+                    *terrain = terrain_asset.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/*
+Water is made of several areas that can overlap or touch, so we count the areas
+the player and boids are in and only take `InWater` away when they have left the
+last of them. Projectiles lose their speed once, when they hit the water, which is
+when all the water they touch is water they just started touching.
+ */
+pub fn water_contact_listener(
+    mut collision_started_reader: EventReader<CollisionStarted>,
+    mut collision_ended_reader: EventReader<CollisionEnded>,
+    water_query: Query<(), With<WaterArea>>,
+    mut wader_query: Query<Option<&mut InWater>, Or<(With<Player>, With<Boid>)>>,
+    mut projectile_query: Query<(&mut LinearVelocity, &CollidingEntities), With<Projectile>>,
+    terrain: Res<Terrain>,
+    mut commands: Commands,
+) {
+    let mut entered: HashMap<Entity, i32> = HashMap::new();
+    let mut changes: HashMap<Entity, i32> = HashMap::new();
+    let other = |entity1: Entity, entity2: Entity| {
+        if water_query.contains(entity1) {
+            Some(entity2)
+        } else if water_query.contains(entity2) {
+            Some(entity1)
+        } else {
+            None
+        }
+    };
+    for CollisionStarted(entity1, entity2) in collision_started_reader.iter() {
+        if let Some(entity) = other(*entity1, *entity2) {
+            *entered.entry(entity).or_default() += 1;
+            *changes.entry(entity).or_default() += 1;
+        }
+    }
+    for CollisionEnded(entity1, entity2) in collision_ended_reader.iter() {
+        if let Some(entity) = other(*entity1, *entity2) {
+            *changes.entry(entity).or_default() -= 1;
+        }
+    }
+
+    for (entity, entered) in entered {
+        let Ok((mut linear_velocity, colliding_entities)) = projectile_query.get_mut(entity) else { continue; };
+        let touching = colliding_entities.iter().filter(|other| water_query.contains(**other)).count() as i32;
+        if entered >= touching {
+            linear_velocity.0 *= terrain.water.projectile_speed;
+        }
+    }
+
+    // Only the player and boids are tracked, and neither is despawned before the commands are applied
+    for (entity, change) in changes {
+        let Ok(in_water) = wader_query.get_mut(entity) else { continue; };
+        let areas = in_water
+            .as_ref()
+            .map(|in_water| in_water.areas as i32)
+            .unwrap_or(0);
+        match (in_water, (areas + change).max(0) as usize) {
+            (Some(_), 0) => {
+                commands.entity(entity).remove::<InWater>();
+            }
+            (Some(mut in_water), areas) => {
+                in_water.areas = areas;
+            }
+            (None, 0) => {}
+            (None, areas) => {
+                commands.entity(entity).insert(InWater { areas });
+            }
+        }
+    }
+}
+
+pub fn slow_player_in_water(
+    mut query: Query<&mut LinearVelocity, (With<Player>, With<InWater>)>,
+    terrain: Res<Terrain>,
+) {
+    for mut linear_velocity in query.iter_mut() {
+        linear_velocity.0 *= terrain.water.player_speed;
+    }
+}
+
+/// Boids that avoid water treat it like a wall.
+pub fn keep_boids_out_of_water(
+    mut collision_reader: EventReader<Collision>,
+    terrain: Res<Terrain>,
+    water_query: Query<&Position, With<WaterArea>>,
+    mut boid_query: Query<&mut Position, (With<Boid>, Without<WaterArea>)>,
+) {
+    if !terrain.water.boids_avoid {
+        return;
+    }
+    for Collision(contact) in collision_reader.iter() {
+        let (water, boid) = if water_query.contains(contact.entity1) {
+            (contact.entity1, contact.entity2)
+        } else {
+            (contact.entity2, contact.entity1)
+        };
+        let Ok(water_position) = water_query.get(water) else { continue; };
+        let Ok(mut boid_position) = boid_query.get_mut(boid) else { continue; };
+        push_away(&mut boid_position, water_position, contact);
+    }
+}
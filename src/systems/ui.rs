@@ -0,0 +1,55 @@
+use bevy::hierarchy::{BuildChildren, DespawnRecursiveExt};
+use bevy::prelude::{AlignItems, Color, Commands, default, Entity, JustifyContent, NodeBundle, Query, Style, TextAlignment, TextBundle, TextStyle, Val, With};
+use crate::components::general::StateScreen;
+
+pub fn spawn_main_menu_screen(commands: Commands) {
+    spawn_state_screen(commands, "Rusty Apocalypse\nPress Enter to play", Color::rgba(0.0, 0.0, 0.0, 0.9));
+}
+
+pub fn spawn_pause_screen(commands: Commands) {
+    spawn_state_screen(commands, "Paused\nPress Escape to resume, Q to quit to the menu", Color::rgba(0.0, 0.0, 0.0, 0.4));
+}
+
+pub fn spawn_game_over_screen(commands: Commands) {
+    spawn_state_screen(commands, "Game over\nPress Enter to play again, Escape for the menu", Color::rgba(0.0, 0.0, 0.0, 0.6));
+}
+
+fn spawn_state_screen(mut commands: Commands, text: &str, background: Color) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: background.into(),
+                ..default()
+            },
+            StateScreen {},
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size: 48.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                    .with_text_alignment(TextAlignment::Center),
+            );
+        });
+}
+
+pub fn despawn_state_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<StateScreen>>,
+) {
+    for screen in query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+}
@@ -0,0 +1,72 @@
+use bevy::asset::{AssetEvent, AssetServer, Assets};
+use bevy::log::info;
+use bevy::prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, ResMut, Time};
+use crate::components::control::ReloadWeapon;
+use crate::components::player::WeaponInventory;
+use crate::events::ammo::{ReloadFinishedEvent, ReloadStartedEvent};
+use crate::components::weapon::{CurrentWeapon, WeaponDefs, WeaponDefsAsset, WeaponDefsFile};
+
+pub fn load_weapon_defs(
+    asset_server: Res<AssetServer>,
+    mut weapon_defs_file: ResMut<WeaponDefsFile>,
+) {
+    let handle = asset_server.load(weapon_defs_file.path.as_str());
+    weapon_defs_file.handle = handle;
+}
+
+/// Replaces `WeaponDefs` whenever the weapon file is loaded or hot reloaded, and updates
+/// the weapons everyone is carrying to match.
+pub fn apply_weapon_defs(
+    mut asset_events: EventReader<AssetEvent<WeaponDefsAsset>>,
+    weapon_defs_file: Res<WeaponDefsFile>,
+    weapon_defs_assets: Res<Assets<WeaponDefsAsset>>,
+    mut weapon_defs: ResMut<WeaponDefs>,
+    mut query: Query<(&mut WeaponInventory, &mut CurrentWeapon)>,
+) {
+    for event in asset_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } if *handle == weapon_defs_file.handle => {
+                if let Some(weapon_defs_asset) = weapon_defs_assets.get(handle) {
+                    info!("Loaded {} weapons from {}", weapon_defs_asset.weapons.len(), weapon_defs_file.path);
+                    weapon_defs.defs = weapon_defs_asset.weapons.clone();
+                    for (mut weapon_inventory, mut current_weapon) in query.iter_mut() {
+                        weapon_inventory.update_from_defs(&mut current_weapon, &weapon_defs.defs);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Starts a reload when asked to, or when the magazine runs dry, and finishes it once
+/// the weapon's reload time has passed.
+pub fn reload_system(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut CurrentWeapon, Option<&ReloadWeapon>)>,
+    mut commands: Commands,
+    mut reload_started_writer: EventWriter<ReloadStartedEvent>,
+    mut reload_finished_writer: EventWriter<ReloadFinishedEvent>,
+) {
+    for (shooter, mut current_weapon, reload_weapon) in query.iter_mut() {
+        if reload_weapon.is_some() {
+            commands.entity(shooter).remove::<ReloadWeapon>();
+        }
+        if (reload_weapon.is_some() || current_weapon.needs_reload()) && current_weapon.start_reload() {
+            if let Some(weapon) = current_weapon.weapon.as_ref() {
+                reload_started_writer.send(ReloadStartedEvent {
+                    shooter,
+                    weapon: weapon.name.clone(),
+                });
+            }
+        }
+        if current_weapon.tick_reload(time.delta_seconds()) {
+            if let Some(weapon) = current_weapon.weapon.as_ref() {
+                reload_finished_writer.send(ReloadFinishedEvent {
+                    shooter,
+                    weapon: weapon.name.clone(),
+                });
+            }
+        }
+    }
+}